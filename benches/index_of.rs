@@ -1,3 +1,4 @@
+#![cfg(target_arch = "x86_64")]
 #![feature(test)]
 #![feature(portable_simd)]
 extern crate core;
@@ -6,61 +7,101 @@ extern crate test;
 use std::hint::black_box;
 
 use simd_http::buffer::{Buffer, BufferSlice};
-use simd_http::utils::avx;
+use simd_http::utils::{avx, avx2, dispatch};
+use simd_http::utils::dispatch::Isa;
 
 static DATA: &[u8] = b"HTTP/1.1\r\nHost: developer.mozilla.org\r\nAccept-Language: fr\r\nUser-Agent: curl/7.64.1\r\nAccept: */*\r\nScheme: http\r\nCache-Control: max-age=0\r\nUpgrade-Insecure-Requests: 1\r\nConnection: keep-alive\r\nSec-Ch-Ua-Arch: x86\r\nSec-Ch-Ua-Mobile: ?0\r\nUser-Agent: Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/108.0.0.0 Safari/537.36\r\nAccept-Encoding: gzip, deflate, br\r\nSec-Fetch-Site: same-origin\r\nSec-Fetch-Mode: cors\r\nSec-Fetch-Dest: empty\r\nSec-Fetch-User: ?1\r\nSec-Fetch-User: ?1\r\nAccept-Language: en-US,en;q=0.9\r\n\r\n";
 static NEEDLE: &[u8; 65] = b"-origin\r\nSec-Fetch-Mode: cors\r\nSec-Fetch-Dest: empty\r\nSec-Fetch-U";
 const WHERE: usize = 406;
+static LONG_DATA: &[u8] = b"POST /upload HTTP/1.1\r\nHost: developer.mozilla.org\r\nUser-Agent: Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/108.0.0.0 Safari/537.36\r\nAccept: */*\r\nAccept-Encoding: gzip, deflate, br\r\nContent-Type: multipart/form-data; boundary=----WebKitFormBoundary7MA4YWxkTrZu0gW7MA4YWxkTrZu0gWxQpL3nR8sV2mJ9cH4t\r\nContent-Length: 403\r\n\r\n------WebKitFormBoundary7MA4YWxkTrZu0gW7MA4YWxkTrZu0gWxQpL3nR8sV2mJ9cH4t\r\nContent-Disposition: form-data; name=\"file\"; filename=\"index.html\"\r\nContent-Type: text/html\r\n\r\n<!DOCTYPE html>\r\n<html lang=\"fr\">\r\n<head><meta charset=\"utf-8\"><title>developer.mozilla.org</title></head>\r\n<body><p>Accept-Language: fr</p></body>\r\n</html>\r\n------WebKitFormBoundary7MA4YWxkTrZu0gW7MA4YWxkTrZu0gWxQpL3nR8sV2mJ9cH4t--\r\n";
+static LONG_NEEDLE: &[u8; 256] = b"------WebKitFormBoundary7MA4YWxkTrZu0gW7MA4YWxkTrZu0gWxQpL3nR8sV2mJ9cH4t\r\nContent-Disposition: form-data; name=\"file\"; filename=\"index.html\"\r\nContent-Type: text/html\r\n\r\n<!DOCTYPE html>\r\n<html lang=\"fr\">\r\n<head><meta charset=\"utf-8\"><title>developer.mozilla";
+const LONG_WHERE: usize = 355;
 
 macro_rules! avx_search_n {
     ($name:ident,$len:literal) => {
+        avx_search_n!($name, $len, DATA, NEEDLE, WHERE);
+    };
+    ($name:ident,$len:literal,$data:expr,$needle:expr,$where:expr) => {
         #[bench]
         fn $name(b: &mut test::Bencher) {
-            let vector = black_box(DATA);
+            if dispatch::detect() < Isa::Avx512 {
+                return;
+            }
+            let vector = black_box($data);
             let mut needle = [0; $len];
-            needle.copy_from_slice(&NEEDLE[..$len]);
+            needle.copy_from_slice(&$needle[..$len]);
             let buffer = BufferSlice::<1024, 4096>::from_slice(vector) ;
 
             let buffer = black_box(buffer);
             let needle = black_box(needle);
             b.iter(|| {
                 let idx = unsafe { avx::search::avx_search(&buffer, &needle) };
-                assert_eq!(WHERE, idx);
+                assert_eq!($where, idx);
+            });
+        }
+    };
+}
+macro_rules! avx2_search_n {
+    ($name:ident,$len:literal) => {
+        avx2_search_n!($name, $len, DATA, NEEDLE, WHERE);
+    };
+    ($name:ident,$len:literal,$data:expr,$needle:expr,$where:expr) => {
+        #[bench]
+        fn $name(b: &mut test::Bencher) {
+            if dispatch::detect() < Isa::Avx2 {
+                return;
+            }
+            let vector = black_box($data);
+            let mut needle = [0; $len];
+            needle.copy_from_slice(&$needle[..$len]);
+            let buffer = BufferSlice::<1024, 4096>::from_slice(vector);
+
+            let buffer = black_box(buffer);
+            let needle = black_box(needle);
+            b.iter(|| {
+                let idx = unsafe { avx2::search::avx2_search(&buffer, &needle) };
+                assert_eq!($where, idx);
             });
         }
     };
 }
 macro_rules! memmem_search_n {
     ($name:ident,$len:literal) => {
+        memmem_search_n!($name, $len, DATA, NEEDLE, WHERE);
+    };
+    ($name:ident,$len:literal,$data:expr,$needle:expr,$where:expr) => {
         #[bench]
         fn $name(b: &mut test::Bencher) {
-            let vector = black_box(DATA);
+            let vector = black_box($data);
             let mut needle = [0; $len];
-            needle.copy_from_slice(&NEEDLE[..$len]);
+            needle.copy_from_slice(&$needle[..$len]);
             let buffer = BufferSlice::<1024, 4096>::from_slice(vector);
             let buffer = black_box(buffer);
             let needle = black_box(needle);
             b.iter(|| {
                 let idx = memchr::memmem::find(&buffer, &needle);
-                assert_eq!(Some(WHERE), idx);
+                assert_eq!(Some($where), idx);
             });
         }
     };
 }
 macro_rules! slice_window_search_n {
     ($name:ident,$len:literal) => {
+        slice_window_search_n!($name, $len, DATA, NEEDLE, WHERE);
+    };
+    ($name:ident,$len:literal,$data:expr,$needle:expr,$where:expr) => {
         #[bench]
         fn $name(b: &mut test::Bencher) {
-            let vector = black_box(DATA);
+            let vector = black_box($data);
             let mut needle = [0; $len];
-            needle.copy_from_slice(&NEEDLE[..$len]);
+            needle.copy_from_slice(&$needle[..$len]);
             let buffer =  BufferSlice::<1024, 4096>::from_slice(vector);
             let buffer = black_box(buffer);
             let needle = black_box(needle);
             b.iter(|| {
                 let idx = buffer.windows(needle.len())
                     .position(|subslice| subslice == needle);
-                assert_eq!(Some(WHERE), idx);
+                assert_eq!(Some($where), idx);
             });
         }
     };
@@ -85,6 +126,9 @@ fn memmem_index_of_01(b: &mut test::Bencher) {
 }
 #[bench]
 fn avx512_index_of_01(b: &mut test::Bencher) {
+    if dispatch::detect() < Isa::Avx512 {
+        return;
+    }
     let vector = black_box(DATA);
     let needle = black_box([NEEDLE[0]]);
     b.iter(|| {
@@ -92,259 +136,92 @@ fn avx512_index_of_01(b: &mut test::Bencher) {
         assert_eq!(result, 45);
     });
 }
+#[bench]
+fn avx2_index_of_01(b: &mut test::Bencher) {
+    if dispatch::detect() < Isa::Avx2 {
+        return;
+    }
+    let buffer = black_box(BufferSlice::<1024, 4096>::from_slice(DATA));
+    let needle = black_box([NEEDLE[0]]);
+    b.iter(|| {
+        let result = black_box(unsafe { avx2::search::avx2_search(&buffer, &needle) });
+        assert_eq!(result, 45);
+    });
+}
 
-slice_window_search_n!(slice_window_index_of_02, 2);
-memmem_search_n!(memmem_index_of_02, 2);
-avx_search_n!(avx512_index_of_02, 2);
-
-slice_window_search_n!(slice_window_index_of_03, 3);
-memmem_search_n!(memmem_index_of_03, 3);
-avx_search_n!(avx512_index_of_03, 3);
-
-slice_window_search_n!(slice_window_index_of_04, 4);
-memmem_search_n!(memmem_index_of_04, 4);
-avx_search_n!(avx512_index_of_04, 4);
-
-slice_window_search_n!(slice_window_index_of_05, 5);
-memmem_search_n!(memmem_index_of_05, 5);
-avx_search_n!(avx512_index_of_05, 5);
+/// Generate module of benches for each needle length, e.g. `index_of_09::avx2`.
+macro_rules! search_benches {
+    ($($module:ident: $len:literal),+ $(,)?) => {
+        $(search_benches!(@module $module, $len, DATA, NEEDLE, WHERE);)+
+    };
+    (long $($module:ident: $len:literal),+ $(,)?) => {
+        $(search_benches!(@module $module, $len, LONG_DATA, LONG_NEEDLE, LONG_WHERE);)+
+    };
+    (@module $module:ident, $len:literal, $data:expr, $needle:expr, $where:expr) => {
+        mod $module {
+            use super::*;
 
-slice_window_search_n!(slice_window_index_of_06, 6);
-memmem_search_n!(memmem_index_of_06, 6);
-avx_search_n!(avx512_index_of_06, 6);
+            slice_window_search_n!(slice_window, $len, $data, $needle, $where);
+            memmem_search_n!(memmem, $len, $data, $needle, $where);
+            avx_search_n!(avx512, $len, $data, $needle, $where);
+            avx2_search_n!(avx2, $len, $data, $needle, $where);
+        }
+    };
+}
 
-slice_window_search_n!(slice_window_index_of_07, 7);
-memmem_search_n!(memmem_index_of_07, 7);
-avx_search_n!(avx512_index_of_07, 7);
+// every needle length handled by a specialized kernel, up to 65 bytes
+search_benches! {
+    index_of_02: 2, index_of_03: 3, index_of_04: 4, index_of_05: 5, index_of_06: 6, index_of_07: 7,
+    index_of_08: 8, index_of_09: 9, index_of_10: 10, index_of_11: 11, index_of_12: 12, index_of_13: 13,
+    index_of_14: 14, index_of_15: 15, index_of_16: 16, index_of_17: 17, index_of_18: 18, index_of_19: 19,
+    index_of_20: 20, index_of_21: 21, index_of_22: 22, index_of_23: 23, index_of_24: 24, index_of_25: 25,
+    index_of_26: 26, index_of_27: 27, index_of_28: 28, index_of_29: 29, index_of_30: 30, index_of_31: 31,
+    index_of_32: 32, index_of_33: 33, index_of_34: 34, index_of_35: 35, index_of_36: 36, index_of_37: 37,
+    index_of_38: 38, index_of_39: 39, index_of_40: 40, index_of_41: 41, index_of_42: 42, index_of_43: 43,
+    index_of_44: 44, index_of_45: 45, index_of_46: 46, index_of_47: 47, index_of_48: 48, index_of_49: 49,
+    index_of_50: 50, index_of_51: 51, index_of_52: 52, index_of_53: 53, index_of_54: 54, index_of_55: 55,
+    index_of_56: 56, index_of_57: 57, index_of_58: 58, index_of_59: 59, index_of_60: 60, index_of_61: 61,
+    index_of_62: 62, index_of_63: 63, index_of_64: 64, index_of_65: 65,
+}
 
-slice_window_search_n!(slice_window_index_of_08, 8);
-memmem_search_n!(memmem_index_of_08, 8);
-avx_search_n!(avx512_index_of_08, 8);
-//
-// slice_window_search_n!(slice_window_index_of_09, 9);
-// memmem_search_n!(memmem_index_of_09, 9);
-// avx_search_n!(avx512_index_of_09, 9);
-//
-// slice_window_search_n!(slice_window_index_of_10, 10);
-// memmem_search_n!(memmem_index_of_10, 10);
-// avx_search_n!(avx512_index_of_10, 10);
-//
-// slice_window_search_n!(slice_window_index_of_11, 11);
-// memmem_search_n!(memmem_index_of_11, 11);
-// avx_search_n!(avx512_index_of_11, 11);
-//
-// slice_window_search_n!(slice_window_index_of_12, 12);
-// memmem_search_n!(memmem_index_of_12, 12);
-// avx_search_n!(avx512_index_of_12, 12);
-//
-// slice_window_search_n!(slice_window_index_of_13, 13);
-// memmem_search_n!(memmem_index_of_13, 13);
-// avx_search_n!(avx512_index_of_13, 13);
-//
-// slice_window_search_n!(slice_window_index_of_14, 14);
-// memmem_search_n!(memmem_index_of_14, 14);
-// avx_search_n!(avx512_index_of_14, 14);
-//
-// slice_window_search_n!(slice_window_index_of_15, 15);
-// memmem_search_n!(memmem_index_of_15, 15);
-// avx_search_n!(avx512_index_of_15, 15);
-//
-// slice_window_search_n!(slice_window_index_of_16, 16);
-// memmem_search_n!(memmem_index_of_16, 16);
-// avx_search_n!(avx512_index_of_16, 16);
-//
-// slice_window_search_n!(slice_window_index_of_17, 17);
-// memmem_search_n!(memmem_index_of_17, 17);
-// avx_search_n!(avx512_index_of_17, 17);
-//
-// slice_window_search_n!(slice_window_index_of_18, 18);
-// memmem_search_n!(memmem_index_of_18, 18);
-// avx_search_n!(avx512_index_of_18, 18);
-//
-// slice_window_search_n!(slice_window_index_of_19, 19);
-// memmem_search_n!(memmem_index_of_19, 19);
-// avx_search_n!(avx512_index_of_19, 19);
-//
-// slice_window_search_n!(slice_window_index_of_20, 20);
-// memmem_search_n!(memmem_index_of_20, 20);
-// avx_search_n!(avx512_index_of_20, 20);
-//
-// slice_window_search_n!(slice_window_index_of_21, 21);
-// memmem_search_n!(memmem_index_of_21, 21);
-// avx_search_n!(avx512_index_of_21, 21);
-//
-// slice_window_search_n!(slice_window_index_of_22, 22);
-// memmem_search_n!(memmem_index_of_22, 22);
-// avx_search_n!(avx512_index_of_22, 22);
-//
-// slice_window_search_n!(slice_window_index_of_23, 23);
-// memmem_search_n!(memmem_index_of_23, 23);
-// avx_search_n!(avx512_index_of_23, 23);
-//
-// slice_window_search_n!(slice_window_index_of_24, 24);
-// memmem_search_n!(memmem_index_of_24, 24);
-// avx_search_n!(avx512_index_of_24, 24);
-//
-// slice_window_search_n!(slice_window_index_of_25, 25);
-// memmem_search_n!(memmem_index_of_25, 25);
-// avx_search_n!(avx512_index_of_25, 25);
-//
-// slice_window_search_n!(slice_window_index_of_26, 26);
-// memmem_search_n!(memmem_index_of_26, 26);
-// avx_search_n!(avx512_index_of_26, 26);
-//
-// slice_window_search_n!(slice_window_index_of_27, 27);
-// memmem_search_n!(memmem_index_of_27, 27);
-// avx_search_n!(avx512_index_of_27, 27);
-//
-// slice_window_search_n!(slice_window_index_of_28, 28);
-// memmem_search_n!(memmem_index_of_28, 28);
-// avx_search_n!(avx512_index_of_28, 28);
-//
-// slice_window_search_n!(slice_window_index_of_29, 29);
-// memmem_search_n!(memmem_index_of_29, 29);
-// avx_search_n!(avx512_index_of_29, 29);
-//
-// slice_window_search_n!(slice_window_index_of_30, 30);
-// memmem_search_n!(memmem_index_of_30, 30);
-// avx_search_n!(avx512_index_of_30, 30);
-//
-// slice_window_search_n!(slice_window_index_of_31, 31);
-// memmem_search_n!(memmem_index_of_31, 31);
-// avx_search_n!(avx512_index_of_31, 31);
-//
-// slice_window_search_n!(slice_window_index_of_32, 32);
-// memmem_search_n!(memmem_index_of_32, 32);
-// avx_search_n!(avx512_index_of_32, 32);
-//
-// slice_window_search_n!(slice_window_index_of_33, 33);
-// memmem_search_n!(memmem_index_of_33, 33);
-// avx_search_n!(avx512_index_of_33, 33);
-//
-// slice_window_search_n!(slice_window_index_of_34, 34);
-// memmem_search_n!(memmem_index_of_34, 34);
-// avx_search_n!(avx512_index_of_34, 34);
-//
-// slice_window_search_n!(slice_window_index_of_35, 35);
-// memmem_search_n!(memmem_index_of_35, 35);
-// avx_search_n!(avx512_index_of_35, 35);
-//
-// slice_window_search_n!(slice_window_index_of_36, 36);
-// memmem_search_n!(memmem_index_of_36, 36);
-// avx_search_n!(avx512_index_of_36, 36);
-//
-// slice_window_search_n!(slice_window_index_of_37, 37);
-// memmem_search_n!(memmem_index_of_37, 37);
-// avx_search_n!(avx512_index_of_37, 37);
-//
-// slice_window_search_n!(slice_window_index_of_38, 38);
-// memmem_search_n!(memmem_index_of_38, 38);
-// avx_search_n!(avx512_index_of_38, 38);
-//
-// slice_window_search_n!(slice_window_index_of_39, 39);
-// memmem_search_n!(memmem_index_of_39, 39);
-// avx_search_n!(avx512_index_of_39, 39);
-//
-// slice_window_search_n!(slice_window_index_of_40, 40);
-// memmem_search_n!(memmem_index_of_40, 40);
-// avx_search_n!(avx512_index_of_40, 40);
-//
-// slice_window_search_n!(slice_window_index_of_41, 41);
-// memmem_search_n!(memmem_index_of_41, 41);
-// avx_search_n!(avx512_index_of_41, 41);
-//
-// slice_window_search_n!(slice_window_index_of_42, 42);
-// memmem_search_n!(memmem_index_of_42, 42);
-// avx_search_n!(avx512_index_of_42, 42);
-//
-// slice_window_search_n!(slice_window_index_of_43, 43);
-// memmem_search_n!(memmem_index_of_43, 43);
-// avx_search_n!(avx512_index_of_43, 43);
-//
-// slice_window_search_n!(slice_window_index_of_44, 44);
-// memmem_search_n!(memmem_index_of_44, 44);
-// avx_search_n!(avx512_index_of_44, 44);
-//
-// slice_window_search_n!(slice_window_index_of_45, 45);
-// memmem_search_n!(memmem_index_of_45, 45);
-// avx_search_n!(avx512_index_of_45, 45);
-//
-// slice_window_search_n!(slice_window_index_of_46, 46);
-// memmem_search_n!(memmem_index_of_46, 46);
-// avx_search_n!(avx512_index_of_46, 46);
-//
-// slice_window_search_n!(slice_window_index_of_47, 47);
-// memmem_search_n!(memmem_index_of_47, 47);
-// avx_search_n!(avx512_index_of_47, 47);
-//
-// slice_window_search_n!(slice_window_index_of_48, 48);
-// memmem_search_n!(memmem_index_of_48, 48);
-// avx_search_n!(avx512_index_of_48, 48);
-//
-// slice_window_search_n!(slice_window_index_of_49, 49);
-// memmem_search_n!(memmem_index_of_49, 49);
-// avx_search_n!(avx512_index_of_49, 49);
-//
-// slice_window_search_n!(slice_window_index_of_50, 50);
-// memmem_search_n!(memmem_index_of_50, 50);
-// avx_search_n!(avx512_index_of_50, 50);
-//
-// slice_window_search_n!(slice_window_index_of_51, 51);
-// memmem_search_n!(memmem_index_of_51, 51);
-// avx_search_n!(avx512_index_of_51, 51);
-//
-// slice_window_search_n!(slice_window_index_of_52, 52);
-// memmem_search_n!(memmem_index_of_52, 52);
-// avx_search_n!(avx512_index_of_52, 52);
-//
-// slice_window_search_n!(slice_window_index_of_53, 53);
-// memmem_search_n!(memmem_index_of_53, 53);
-// avx_search_n!(avx512_index_of_53, 53);
-//
-// slice_window_search_n!(slice_window_index_of_54, 54);
-// memmem_search_n!(memmem_index_of_54, 54);
-// avx_search_n!(avx512_index_of_54, 54);
-//
-// slice_window_search_n!(slice_window_index_of_55, 55);
-// memmem_search_n!(memmem_index_of_55, 55);
-// avx_search_n!(avx512_index_of_55, 55);
-//
-// slice_window_search_n!(slice_window_index_of_56, 56);
-// memmem_search_n!(memmem_index_of_56, 56);
-// avx_search_n!(avx512_index_of_56, 56);
-//
-// slice_window_search_n!(slice_window_index_of_57, 57);
-// memmem_search_n!(memmem_index_of_57, 57);
-// avx_search_n!(avx512_index_of_57, 57);
-//
-// slice_window_search_n!(slice_window_index_of_58, 58);
-// memmem_search_n!(memmem_index_of_58, 58);
-// avx_search_n!(avx512_index_of_58, 58);
-//
-// slice_window_search_n!(slice_window_index_of_59, 59);
-// memmem_search_n!(memmem_index_of_59, 59);
-// avx_search_n!(avx512_index_of_59, 59);
-//
-// slice_window_search_n!(slice_window_index_of_60, 60);
-// memmem_search_n!(memmem_index_of_60, 60);
-// avx_search_n!(avx512_index_of_60, 60);
-//
-// slice_window_search_n!(slice_window_index_of_61, 61);
-// memmem_search_n!(memmem_index_of_61, 61);
-// avx_search_n!(avx512_index_of_61, 61);
-//
-// slice_window_search_n!(slice_window_index_of_62, 62);
-// memmem_search_n!(memmem_index_of_62, 62);
-// avx_search_n!(avx512_index_of_62, 62);
-//
-// slice_window_search_n!(slice_window_index_of_63, 63);
-// memmem_search_n!(memmem_index_of_63, 63);
-// avx_search_n!(avx512_index_of_63, 63);
-//
-// slice_window_search_n!(slice_window_index_of_64, 64);
-// memmem_search_n!(memmem_index_of_64, 64);
-// avx_search_n!(avx512_index_of_64, 64);
-//
-// slice_window_search_n!(slice_window_index_of_65, 65);
-// memmem_search_n!(memmem_index_of_65, 65);
-// avx_search_n!(avx512_index_of_65, 65);
\ No newline at end of file
+// every needle length of the long kernel, multipart boundary is up to 70 bytes plus `--` and CRLF
+search_benches! {
+    long
+    index_of_66: 66, index_of_67: 67, index_of_68: 68, index_of_69: 69, index_of_70: 70, index_of_71: 71,
+    index_of_72: 72, index_of_73: 73, index_of_74: 74, index_of_75: 75, index_of_76: 76, index_of_77: 77,
+    index_of_78: 78, index_of_79: 79, index_of_80: 80, index_of_81: 81, index_of_82: 82, index_of_83: 83,
+    index_of_84: 84, index_of_85: 85, index_of_86: 86, index_of_87: 87, index_of_88: 88, index_of_89: 89,
+    index_of_90: 90, index_of_91: 91, index_of_92: 92, index_of_93: 93, index_of_94: 94, index_of_95: 95,
+    index_of_96: 96, index_of_97: 97, index_of_98: 98, index_of_99: 99, index_of_100: 100, index_of_101: 101,
+    index_of_102: 102, index_of_103: 103, index_of_104: 104, index_of_105: 105, index_of_106: 106,
+    index_of_107: 107, index_of_108: 108, index_of_109: 109, index_of_110: 110, index_of_111: 111,
+    index_of_112: 112, index_of_113: 113, index_of_114: 114, index_of_115: 115, index_of_116: 116,
+    index_of_117: 117, index_of_118: 118, index_of_119: 119, index_of_120: 120, index_of_121: 121,
+    index_of_122: 122, index_of_123: 123, index_of_124: 124, index_of_125: 125, index_of_126: 126,
+    index_of_127: 127, index_of_128: 128, index_of_129: 129, index_of_130: 130, index_of_131: 131,
+    index_of_132: 132, index_of_133: 133, index_of_134: 134, index_of_135: 135, index_of_136: 136,
+    index_of_137: 137, index_of_138: 138, index_of_139: 139, index_of_140: 140, index_of_141: 141,
+    index_of_142: 142, index_of_143: 143, index_of_144: 144, index_of_145: 145, index_of_146: 146,
+    index_of_147: 147, index_of_148: 148, index_of_149: 149, index_of_150: 150, index_of_151: 151,
+    index_of_152: 152, index_of_153: 153, index_of_154: 154, index_of_155: 155, index_of_156: 156,
+    index_of_157: 157, index_of_158: 158, index_of_159: 159, index_of_160: 160, index_of_161: 161,
+    index_of_162: 162, index_of_163: 163, index_of_164: 164, index_of_165: 165, index_of_166: 166,
+    index_of_167: 167, index_of_168: 168, index_of_169: 169, index_of_170: 170, index_of_171: 171,
+    index_of_172: 172, index_of_173: 173, index_of_174: 174, index_of_175: 175, index_of_176: 176,
+    index_of_177: 177, index_of_178: 178, index_of_179: 179, index_of_180: 180, index_of_181: 181,
+    index_of_182: 182, index_of_183: 183, index_of_184: 184, index_of_185: 185, index_of_186: 186,
+    index_of_187: 187, index_of_188: 188, index_of_189: 189, index_of_190: 190, index_of_191: 191,
+    index_of_192: 192, index_of_193: 193, index_of_194: 194, index_of_195: 195, index_of_196: 196,
+    index_of_197: 197, index_of_198: 198, index_of_199: 199, index_of_200: 200, index_of_201: 201,
+    index_of_202: 202, index_of_203: 203, index_of_204: 204, index_of_205: 205, index_of_206: 206,
+    index_of_207: 207, index_of_208: 208, index_of_209: 209, index_of_210: 210, index_of_211: 211,
+    index_of_212: 212, index_of_213: 213, index_of_214: 214, index_of_215: 215, index_of_216: 216,
+    index_of_217: 217, index_of_218: 218, index_of_219: 219, index_of_220: 220, index_of_221: 221,
+    index_of_222: 222, index_of_223: 223, index_of_224: 224, index_of_225: 225, index_of_226: 226,
+    index_of_227: 227, index_of_228: 228, index_of_229: 229, index_of_230: 230, index_of_231: 231,
+    index_of_232: 232, index_of_233: 233, index_of_234: 234, index_of_235: 235, index_of_236: 236,
+    index_of_237: 237, index_of_238: 238, index_of_239: 239, index_of_240: 240, index_of_241: 241,
+    index_of_242: 242, index_of_243: 243, index_of_244: 244, index_of_245: 245, index_of_246: 246,
+    index_of_247: 247, index_of_248: 248, index_of_249: 249, index_of_250: 250, index_of_251: 251,
+    index_of_252: 252, index_of_253: 253, index_of_254: 254, index_of_255: 255, index_of_256: 256,
+}
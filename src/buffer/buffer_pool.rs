@@ -1,10 +1,167 @@
-use std::cell::UnsafeCell;
+use monoio::buf::{IoBuf, IoBufMut};
+use std::cell::{Cell, UnsafeCell};
 use std::collections::VecDeque;
+use std::fmt::{Debug, Formatter};
+use std::mem::ManuallyDrop;
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
 
-use super::Buffer;
+use super::{Buffer, BufferSlice};
+
+/// Retention limits of [BufferPool], default is unbounded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolLimits {
+    /// Buffers returned while this many are retained are released to the allocator.
+    pub max_retained: usize,
+    /// [BufferPool::trim] does nothing until more than this many buffers are retained.
+    pub high_watermark: usize,
+    /// [BufferPool::trim] release buffers until this many are retained.
+    pub low_watermark: usize,
+}
+
+impl PoolLimits {
+    pub const fn default() -> Self {
+        Self {
+            max_retained: usize::MAX,
+            high_watermark: usize::MAX,
+            low_watermark: usize::MAX,
+        }
+    }
+}
+
+/// Counters of [BufferPool], used to tune pool size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PoolStats {
+    /// Take served from retained buffers.
+    pub hits: u64,
+    /// Take that found the pool empty.
+    pub misses: u64,
+    /// Buffers released to the allocator by [BufferPool::put] and [BufferPool::trim].
+    pub released: u64,
+    /// Buffers taken and not returned yet.
+    pub outstanding: usize,
+    /// Highest value of `outstanding`.
+    pub peak_outstanding: usize,
+    /// Buffers currently retained.
+    pub retained: usize,
+}
+
+/// Retained buffers of one size with their limits and stats, shared by [BufferPool]
+/// and each class of [super::size_class::SizeClassPool].
+pub(crate) struct Retained<T> {
+    buffers: UnsafeCell<VecDeque<T>>,
+    pub(crate) limits: PoolLimits,
+    stats: Cell<PoolStats>,
+}
+
+impl<T> Retained<T> {
+    pub(crate) const fn new(limits: PoolLimits) -> Self {
+        Self {
+            buffers: UnsafeCell::new(VecDeque::new()),
+            limits,
+            stats: Cell::new(PoolStats {
+                hits: 0,
+                misses: 0,
+                released: 0,
+                outstanding: 0,
+                peak_outstanding: 0,
+                retained: 0,
+            }),
+        }
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    #[inline(always)]
+    unsafe fn buffers(&self) -> &mut VecDeque<T> {
+        &mut *self.buffers.get()
+    }
+
+    #[inline(always)]
+    fn update(&self, f: impl FnOnce(&mut PoolStats)) {
+        let mut stats = self.stats.get();
+        f(&mut stats);
+        self.stats.set(stats);
+    }
+
+    #[inline]
+    pub(crate) fn limits(&self) -> &PoolLimits {
+        &self.limits
+    }
+
+    #[inline]
+    pub(crate) fn stats(&self) -> PoolStats {
+        let mut stats = self.stats.get();
+        stats.retained = unsafe { self.buffers().len() };
+        stats
+    }
+
+    /// Allocate buffers with `allocate` until `amount` are retained, capped by [PoolLimits::max_retained].
+    pub(crate) fn pre_allocate(&self, amount: usize, mut allocate: impl FnMut() -> T) {
+        let amount = amount.min(self.limits.max_retained);
+        let buffers = unsafe { self.buffers() };
+        buffers.reserve(amount.saturating_sub(buffers.len()));
+        for _ in buffers.len()..amount {
+            buffers.push_back(allocate());
+        }
+    }
+
+    pub(crate) fn try_take(&self) -> Option<T> {
+        let buffer = unsafe { self.buffers().pop_front() };
+        self.update(|stats| {
+            if buffer.is_some() {
+                stats.hits += 1;
+                stats.outstanding += 1;
+                stats.peak_outstanding = stats.peak_outstanding.max(stats.outstanding);
+            } else {
+                stats.misses += 1;
+            }
+        });
+        buffer
+    }
+
+    /// Take retained buffer, or the one from `allocate` if none is retained.
+    pub(crate) fn take(&self, allocate: impl FnOnce() -> T) -> T {
+        if let Some(buffer) = self.try_take() {
+            buffer
+        } else {
+            self.update(|stats| {
+                stats.outstanding += 1;
+                stats.peak_outstanding = stats.peak_outstanding.max(stats.outstanding);
+            });
+            allocate()
+        }
+    }
+
+    pub(crate) fn put(&self, buffer: T) {
+        let buffers = unsafe { self.buffers() };
+        let retain = buffers.len() < self.limits.max_retained;
+        if retain {
+            buffers.push_back(buffer);
+        } else {
+            drop(buffer);
+        }
+        self.update(|stats| {
+            // buffer may not come from this pool
+            stats.outstanding = stats.outstanding.saturating_sub(1);
+            stats.released += u64::from(!retain);
+        });
+    }
+
+    pub(crate) fn trim(&self) -> usize {
+        let buffers = unsafe { self.buffers() };
+        if buffers.len() <= self.limits.high_watermark {
+            return 0;
+        }
+        let released = buffers.len() - self.limits.low_watermark.min(buffers.len());
+        buffers.truncate(buffers.len() - released);
+        buffers.shrink_to(self.limits.low_watermark);
+        self.update(|stats| stats.released += released as u64);
+        released
+    }
+}
 
 pub struct BufferPool<const SIZE: usize, const ALIGN: usize> {
-    buffers: UnsafeCell<VecDeque<Buffer<SIZE, ALIGN>>>,
+    retained: Retained<Buffer<SIZE, ALIGN>>,
     // !Send
     _marker: std::marker::PhantomData<*mut ()>,
 }
@@ -17,43 +174,155 @@ impl BufferPool<0, 4096> {
 
 impl<const SIZE: usize, const ALIGN: usize> BufferPool<SIZE, ALIGN> {
     pub const fn default() -> Self {
+        Self::with_limits(PoolLimits::default())
+    }
+
+    pub const fn with_limits(limits: PoolLimits) -> Self {
         Self {
-            buffers: UnsafeCell::new(VecDeque::new()),
+            retained: Retained::new(limits),
             _marker: std::marker::PhantomData,
         }
     }
 
+    #[inline]
+    pub fn limits(&self) -> &PoolLimits {
+        self.retained.limits()
+    }
+
+    #[inline]
+    pub fn stats(&self) -> PoolStats {
+        self.retained.stats()
+    }
+
+    /// Allocate buffers until `amount` are retained, capped by [PoolLimits::max_retained].
     /// # Safety
     /// Caller must ensure size and align are power of two.
     #[inline]
     pub unsafe fn pre_allocate(&self, amount: usize) {
-        let buffer = self.buffer();
-        buffer.reserve(amount);
-        for _ in buffer.len()..amount {
-            buffer.push_back(Buffer::allocate_unchecked());
-        }
+        self.retained.pre_allocate(amount, || Buffer::allocate_unchecked());
     }
 
-    #[allow(clippy::mut_from_ref)]
-    unsafe fn buffer(&self) -> &mut VecDeque<Buffer<SIZE, ALIGN>> {
-        &mut *self.buffers.get()
+    /// Take retained buffer without allocating, None if the pool is empty.
+    #[inline]
+    pub fn try_take(&self) -> Option<Buffer<SIZE, ALIGN>> {
+        self.retained.try_take()
     }
 
     /// # Safety
     /// Caller must ensure size and align are power of two.
-    /// Take buffer with size and alignment garanteed to be the same as the pool.
+    /// Take buffer with size and alignment garanteed to be the same as the pool,
+    /// allocate new one if the pool is empty.
+    #[inline]
     pub unsafe fn take(&self) -> Buffer<SIZE, ALIGN> {
-        if let Some(buffer) = self.buffer().pop_front() {
-            buffer
-        } else {
-            Buffer::allocate_unchecked()
+        self.retained.take(|| Buffer::allocate_unchecked())
+    }
+
+    /// Take buffer as [PooledBuffer] that return itself to this pool on drop, allocate new one if the pool is empty.
+    /// Slice of the buffer start at offset 0 with length 0.
+    pub fn take_pooled(self: &Rc<Self>) -> PooledBuffer<SIZE, ALIGN> {
+        if !ALIGN.is_power_of_two() {
+            panic!("ALIGN was {ALIGN} but must be power of two");
+        }
+        // # Safety
+        // alignment is checked above
+        let buffer = unsafe { self.take() };
+        PooledBuffer {
+            slice: ManuallyDrop::new(BufferSlice::new(buffer, 0, 0)),
+            pool: Rc::clone(self),
         }
     }
 
+    /// Return buffer to the pool, it is released instead when [PoolLimits::max_retained] buffers are retained.
     /// # Safety
     /// buffer must have same size and alignment as the pool.
+    #[inline]
     pub unsafe fn put(&self, buffer: Buffer<SIZE, ALIGN>) {
-        self.buffer().push_back(buffer);
+        self.retained.put(buffer);
+    }
+
+    /// Release buffers back to the allocator until [PoolLimits::low_watermark] are retained
+    /// if more than [PoolLimits::high_watermark] are retained, return amount released.
+    #[inline]
+    pub fn trim(&self) -> usize {
+        self.retained.trim()
+    }
+}
+
+/// Buffer taken by [BufferPool::take_pooled], return itself to the originating pool on drop.
+///
+/// Pool is shared by [Rc] so the buffer can be moved into monoio IO operations across tasks of the same thread.
+pub struct PooledBuffer<const SIZE: usize, const ALIGN: usize> {
+    slice: ManuallyDrop<BufferSlice<SIZE, ALIGN>>,
+    pool: Rc<BufferPool<SIZE, ALIGN>>,
+}
+
+impl<const SIZE: usize, const ALIGN: usize> PooledBuffer<SIZE, ALIGN> {
+    #[inline(always)]
+    pub fn pool(&self) -> &Rc<BufferPool<SIZE, ALIGN>> {
+        &self.pool
+    }
+}
+
+impl<const SIZE: usize, const ALIGN: usize> Deref for PooledBuffer<SIZE, ALIGN> {
+    type Target = BufferSlice<SIZE, ALIGN>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.slice
+    }
+}
+
+impl<const SIZE: usize, const ALIGN: usize> DerefMut for PooledBuffer<SIZE, ALIGN> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.slice
+    }
+}
+
+impl<const SIZE: usize, const ALIGN: usize> Debug for PooledBuffer<SIZE, ALIGN> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&*self.slice, f)
+    }
+}
+
+impl<const SIZE: usize, const ALIGN: usize> Drop for PooledBuffer<SIZE, ALIGN> {
+    #[inline]
+    fn drop(&mut self) {
+        // # Safety
+        // slice is not used after taken, buffer has the same size and alignment as the pool
+        unsafe {
+            let slice = ManuallyDrop::take(&mut self.slice);
+            self.pool.put(slice.into_inner());
+        }
+    }
+}
+
+unsafe impl<const SIZE: usize, const ALIGN: usize> IoBuf for PooledBuffer<SIZE, ALIGN> {
+    #[inline(always)]
+    fn read_ptr(&self) -> *const u8 {
+        self.slice.read_ptr()
+    }
+
+    #[inline(always)]
+    fn bytes_init(&self) -> usize {
+        self.slice.bytes_init()
+    }
+}
+
+unsafe impl<const SIZE: usize, const ALIGN: usize> IoBufMut for PooledBuffer<SIZE, ALIGN> {
+    #[inline(always)]
+    fn write_ptr(&mut self) -> *mut u8 {
+        self.slice.write_ptr()
+    }
+
+    #[inline(always)]
+    fn bytes_total(&mut self) -> usize {
+        self.slice.bytes_total()
+    }
+
+    #[inline(always)]
+    unsafe fn set_init(&mut self, pos: usize) {
+        self.slice.set_init(pos);
     }
 }
 
@@ -66,7 +335,57 @@ mod tests {
         let pool = BufferPool::new4k::<8192>();
         unsafe {
             pool.pre_allocate(10);
-            assert_eq!(pool.buffer().len(), 10);
+            assert_eq!(pool.stats().retained, 10);
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_limits_and_stats() {
+        let pool = BufferPool::<4096, 4096>::with_limits(PoolLimits {
+            max_retained: 8,
+            high_watermark: 6,
+            low_watermark: 2,
+        });
+        unsafe {
+            pool.pre_allocate(10);
+            assert_eq!(pool.stats().retained, 8);
+
+            let taken = (0..10).map(|_| pool.take()).collect::<Vec<_>>();
+            assert!(pool.try_take().is_none());
+            let stats = pool.stats();
+            assert_eq!((stats.hits, stats.misses, stats.outstanding, stats.peak_outstanding), (8, 3, 10, 10));
+
+            taken.into_iter().for_each(|buffer| pool.put(buffer));
+            let stats = pool.stats();
+            assert_eq!((stats.retained, stats.released, stats.outstanding), (8, 2, 0));
+
+            assert_eq!(pool.trim(), 6);
+            assert_eq!(pool.trim(), 0);
+            let stats = pool.stats();
+            assert_eq!((stats.retained, stats.released, stats.peak_outstanding), (2, 8, 10));
+        }
+    }
+
+    #[test]
+    fn test_pooled_buffer() {
+        let pool = Rc::new(BufferPool::new4k::<4096>());
+        let mut buffer = pool.take_pooled();
+        assert_eq!(buffer.len(), 0);
+        assert_eq!(pool.stats().outstanding, 1);
+        unsafe {
+            std::ptr::copy_nonoverlapping(b"GET".as_ptr(), buffer.write_ptr(), 3);
+            buffer.set_init(3);
+        }
+        assert_eq!(&buffer[..], b"GET");
+        assert_eq!(buffer.read_ptr() as usize % 4096, 0);
+        let ptr = buffer.read_ptr();
+
+        drop(buffer);
+        let stats = pool.stats();
+        assert_eq!((stats.outstanding, stats.retained), (0, 1));
+        // same allocation is reused
+        let buffer = pool.take_pooled();
+        assert_eq!(buffer.read_ptr(), ptr);
+        assert_eq!(pool.stats().hits, 1);
+    }
+}
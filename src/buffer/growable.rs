@@ -0,0 +1,262 @@
+use monoio::buf::{IoBuf, IoBufMut};
+use std::alloc::Layout;
+use std::fmt::{Debug, Formatter};
+use std::ops::{Deref, DerefMut};
+
+use super::{PaddedHaystack, PADDING};
+use crate::utils::alloc::{alloc_u8_aligned, dealloc_u8_aligned, realloc_u8_aligned};
+
+const PROCESS_SIZE: usize = 64;
+
+/// Aligned buffer that grows on demand, capacity is multiple of 64 and always followed by [PADDING] zeroed bytes,
+/// so SIMD search over it never read past the allocation.
+pub struct GrowableBuffer<const ALIGN: usize = 4096> {
+    ptr: *mut u8,
+    len: usize,
+    capacity: usize,
+}
+
+impl GrowableBuffer<4096> {
+    #[inline(always)]
+    pub fn new4k() -> Self {
+        Self::new()
+    }
+}
+
+impl<const ALIGN: usize> Default for GrowableBuffer<ALIGN> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const ALIGN: usize> GrowableBuffer<ALIGN> {
+    /// Buffer with no capacity, only padding is allocated.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// # Panics
+    /// `ALIGN` is not power of two or less than 64.
+    pub fn with_capacity(capacity: usize) -> Self {
+        if !ALIGN.is_power_of_two() || ALIGN < PROCESS_SIZE {
+            panic!("ALIGN was {ALIGN} but must be power of two and at least {PROCESS_SIZE}");
+        }
+        let (capacity, size) = Self::sizes(capacity);
+        // # Safety
+        // align is checked above and size is not zero
+        unsafe {
+            let ptr = alloc_u8_aligned(size, ALIGN);
+            std::ptr::write_bytes(ptr, 0, size);
+            Self { ptr, len: 0, capacity }
+        }
+    }
+
+    /// Round `capacity` up to multiple of 64 and return it with allocation size including [PADDING].
+    /// # Panics
+    /// Allocation size overflow or is not a valid [Layout].
+    #[inline]
+    fn sizes(capacity: usize) -> (usize, usize) {
+        let capacity = capacity.checked_next_multiple_of(PROCESS_SIZE).expect("capacity overflow");
+        let size = capacity.checked_add(PADDING).expect("capacity overflow");
+        Layout::from_size_align(size, ALIGN).expect("capacity overflow");
+        (capacity, size)
+    }
+
+    /// Copy `slice` into new buffer, nothing is truncated.
+    pub fn from_slice(slice: &[u8]) -> Self {
+        let mut buffer = Self::with_capacity(slice.len());
+        buffer.extend_from_slice(slice);
+        buffer
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Usable capacity, [PADDING] is not included.
+    #[inline(always)]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    #[inline(always)]
+    pub fn ptr(&self) -> *mut u8 {
+        self.ptr
+    }
+
+    /// Reallocate with the same alignment so at least `additional` more bytes fit after the data,
+    /// capacity at least doubles to amortize growth.
+    pub fn reserve(&mut self, additional: usize) {
+        let required = self.len.checked_add(additional).expect("capacity overflow");
+        if required <= self.capacity {
+            return;
+        }
+        let (capacity, size) = Self::sizes(required.max(self.capacity.saturating_mul(2)));
+        let old_size = self.capacity + PADDING;
+        // # Safety
+        // size and align are the same when allocated, bytes after old allocation are zeroed
+        // so capacity and padding stay initialized
+        unsafe {
+            self.ptr = realloc_u8_aligned(self.ptr, old_size, ALIGN, size);
+            std::ptr::write_bytes(self.ptr.add(old_size), 0, size - old_size);
+        }
+        self.capacity = capacity;
+    }
+
+    pub fn extend_from_slice(&mut self, data: &[u8]) {
+        self.reserve(data.len());
+        // # Safety
+        // capacity is reserved above
+        unsafe {
+            std::ptr::copy_nonoverlapping(data.as_ptr(), self.ptr.add(self.len), data.len());
+        }
+        self.len += data.len();
+    }
+
+    #[inline]
+    pub fn set_len(&mut self, len: usize) {
+        if len > self.capacity {
+            panic!("len {len} is greater than capacity {}", self.capacity);
+        }
+        self.len = len;
+    }
+
+    /// # Safety
+    /// `len` must not be greater than capacity.
+    #[inline(always)]
+    pub unsafe fn set_len_unchecked(&mut self, len: usize) {
+        self.len = len;
+    }
+
+    /// Shorten the data, capacity is kept.
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Data followed by unused capacity and [PADDING], all initialized.
+    #[inline(always)]
+    pub fn padded(&self) -> &[u8] {
+        // # Safety
+        // memory is zeroed after allocated and grown
+        unsafe { std::slice::from_raw_parts(self.ptr, self.capacity + PADDING) }
+    }
+}
+
+// # Safety
+// allocation is aligned to ALIGN which is at least 64, capacity is multiple of 64 and followed by PADDING zeroed bytes
+unsafe impl<const ALIGN: usize> PaddedHaystack for GrowableBuffer<ALIGN> {
+    #[inline(always)]
+    fn data(&self) -> &[u8] {
+        self
+    }
+
+    #[inline(always)]
+    fn padded(&self) -> &[u8] {
+        GrowableBuffer::padded(self)
+    }
+}
+
+impl<const ALIGN: usize> Drop for GrowableBuffer<ALIGN> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            dealloc_u8_aligned(self.ptr, self.capacity + PADDING, ALIGN);
+        }
+    }
+}
+
+impl<const ALIGN: usize> Debug for GrowableBuffer<ALIGN> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.iter())
+            .finish()
+    }
+}
+
+impl<const ALIGN: usize> Deref for GrowableBuffer<ALIGN> {
+    type Target = [u8];
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+impl<const ALIGN: usize> DerefMut for GrowableBuffer<ALIGN> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+}
+
+unsafe impl<const ALIGN: usize> IoBuf for GrowableBuffer<ALIGN> {
+    #[inline(always)]
+    fn read_ptr(&self) -> *const u8 {
+        self.ptr
+    }
+
+    #[inline(always)]
+    fn bytes_init(&self) -> usize {
+        self.len
+    }
+}
+
+unsafe impl<const ALIGN: usize> IoBufMut for GrowableBuffer<ALIGN> {
+    #[inline(always)]
+    fn write_ptr(&mut self) -> *mut u8 {
+        self.ptr
+    }
+
+    #[inline(always)]
+    fn bytes_total(&mut self) -> usize {
+        self.capacity
+    }
+
+    #[inline(always)]
+    unsafe fn set_init(&mut self, pos: usize) {
+        self.set_len_unchecked(pos);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grow() {
+        let mut buffer = GrowableBuffer::new4k();
+        assert_eq!((buffer.len(), buffer.capacity()), (0, 0));
+        assert_eq!(buffer.padded(), [0; PADDING]);
+
+        let data = (0..10_000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        for chunk in data.chunks(777) {
+            buffer.extend_from_slice(chunk);
+            assert!(buffer.ptr().is_aligned_to(4096));
+            assert_eq!(buffer.capacity() % PROCESS_SIZE, 0);
+            assert!(buffer.padded()[buffer.len()..].iter().all(|&byte| byte == 0));
+        }
+        assert_eq!(&buffer[..], &data[..]);
+
+        let large = GrowableBuffer::<64>::from_slice(&data);
+        assert_eq!((&large[..], large.capacity()), (&data[..], 10_048));
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity overflow")]
+    fn test_capacity_overflow() {
+        GrowableBuffer::<64>::with_capacity(usize::MAX - 10);
+    }
+}
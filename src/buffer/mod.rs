@@ -5,16 +5,37 @@ use std::ffi::CStr;
 use std::fmt::{Debug, Formatter};
 use std::hint::assert_unchecked;
 use std::mem::ManuallyDrop;
-use std::ops::{Deref, DerefMut, Index};
+use std::ops::{Deref, DerefMut, Index, Range};
 use std::simd::cmp::SimdPartialEq;
 use std::simd::{LaneCount, Simd, SimdElement, SupportedLaneCount};
 
 use crate::utils::alloc::{alloc_u8_aligned, dealloc_u8_aligned};
+use crate::utils::simd::decode::{decode_path, DecodeError};
 
 pub mod buffer_pool;
+pub mod growable;
+pub mod registry;
+pub mod size_class;
 
 pub const ALIGN: usize = 4096;
 
+/// Initialized bytes [PaddedHaystack] keep after its data rounded up to 64 bytes,
+/// SIMD search may read one more block and a needle of up to 64 bytes past the end.
+pub const PADDING: usize = 128;
+
+/// Buffer that always keep [PADDING] initialized bytes after its data, so SIMD search over it
+/// can't read out of the allocation and is safe without capacity checks, see [crate::search::find_in].
+/// # Safety
+/// `padded` must start at the same address as `data`, which is 64 bytes aligned,
+/// and its length must be at least length of `data` rounded up to 64 plus [PADDING].
+pub unsafe trait PaddedHaystack {
+    /// Logical content of the buffer.
+    fn data(&self) -> &[u8];
+
+    /// Data followed by the rest of the buffer and the padding.
+    fn padded(&self) -> &[u8];
+}
+
 #[derive(Debug)]
 #[repr(transparent)]
 pub struct Buffer<const LEN: usize, const ALIGN: usize = 4096> {
@@ -67,6 +88,20 @@ impl<const LEN: usize, const ALIGN: usize> Buffer<LEN, ALIGN> {
         }
     }
 
+    #[inline(always)]
+    pub fn as_slice(&self) -> &[u8] {
+        // # Safety
+        // memory is zeroed after allocated
+        unsafe { std::slice::from_raw_parts(self.ptr, LEN) }
+    }
+
+    #[inline(always)]
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        // # Safety
+        // memory is zeroed after allocated and borrowed mutably only once through &mut self
+        unsafe { std::slice::from_raw_parts_mut(self.ptr, LEN) }
+    }
+
     /// # Safety
     /// Caller must ensure block * LANES is less than the buffer length.
     #[inline(always)]
@@ -177,6 +212,7 @@ impl<const LEN: usize, const ALIGN: usize> BufferSlice<LEN, ALIGN> {
         Self { buffer, offset, len }
     }
 
+    /// Copy `slice` into new buffer, bytes after `LEN` are truncated, see [growable::GrowableBuffer] for data of any length.
     pub fn from_slice(slice: &[u8]) -> Self {
         let mut buffer = Buffer::<LEN, ALIGN>::allocate();
         let len = slice.len().min(LEN);
@@ -188,6 +224,14 @@ impl<const LEN: usize, const ALIGN: usize> BufferSlice<LEN, ALIGN> {
         String::from_utf8_lossy(self)
     }
 
+    /// Percent-decode and UTF-8 validate `range` in place, e.g. path from [crate::parser::RequestParser::path_range].
+    /// Escapes of control bytes like `%00` are rejected with [DecodeError::ControlByte].
+    /// Bytes of `range` after the decoded path are left unspecified.
+    #[inline]
+    pub fn decode_in_place(&mut self, range: Range<usize>, plus_as_space: bool) -> Result<&str, DecodeError> {
+        decode_path(&mut self[range], plus_as_space)
+    }
+
     #[inline(always)]
     pub fn buffer(&self) -> &Buffer<LEN, ALIGN> {
         &self.buffer
@@ -0,0 +1,325 @@
+use monoio::buf::{IoBuf, IoBufMut};
+use std::cell::OnceCell;
+use std::fmt::{Debug, Formatter};
+use std::mem::ManuallyDrop;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+use super::buffer_pool::{BufferPool, PoolLimits, PoolStats};
+use super::{Buffer, BufferSlice, ALIGN};
+use crate::limit::MAX_HEAD_LENGTH;
+
+/// Size of buffers managed by the registry, large enough for a whole request head.
+pub const CORE_BUFFER_SIZE: usize = MAX_HEAD_LENGTH;
+
+type CoreBufferInner = Buffer<CORE_BUFFER_SIZE, ALIGN>;
+
+/// Buffer on its way back to the owner core.
+struct Migrated(CoreBufferInner);
+
+// # Safety
+// buffer is the only owner of its allocation
+unsafe impl Send for Migrated {}
+
+/// Buffers dropped on other threads, `pending` let owner check without locking.
+/// `claimed` is set while a thread is registered as this core.
+#[derive(Default)]
+struct Mailbox {
+    claimed: AtomicBool,
+    pending: AtomicUsize,
+    buffers: Mutex<Vec<Migrated>>,
+}
+
+static MAILBOXES: OnceLock<Box<[Mailbox]>> = OnceLock::new();
+
+struct Core {
+    id: usize,
+    pool: BufferPool<CORE_BUFFER_SIZE, ALIGN>,
+}
+
+impl Drop for Core {
+    fn drop(&mut self) {
+        // thread is exiting, the id can be registered again
+        if let Some(mailboxes) = MAILBOXES.get() {
+            mailboxes[self.id].claimed.store(false, Ordering::Release);
+        }
+    }
+}
+
+thread_local! {
+    static CORE: OnceCell<Core> = const { OnceCell::new() };
+}
+
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegistryError {
+    #[error("registry is already initialized with {0} cores")]
+    AlreadyInitialized(usize),
+    #[error("registry is not initialized")]
+    Uninitialized,
+    #[error("core {core} is out of range of {cores} cores")]
+    OutOfRange { core: usize, cores: usize },
+    #[error("current thread is already registered as core {0}")]
+    AlreadyRegistered(usize),
+    #[error("core {0} is already registered by another thread")]
+    CoreInUse(usize),
+}
+
+/// Create mailbox of every core, must be called once before worker threads are spawned.
+pub fn init(cores: usize) -> Result<(), RegistryError> {
+    let mut initialized = true;
+    let mailboxes = MAILBOXES.get_or_init(|| {
+        initialized = false;
+        (0..cores).map(|_| Mailbox::default()).collect()
+    });
+    if initialized {
+        return Err(RegistryError::AlreadyInitialized(mailboxes.len()));
+    }
+    Ok(())
+}
+
+/// Register current thread as `core` with its own pool, called by each monoio worker at startup.
+/// Each id can only be registered by one thread at a time, it is released when that thread exits.
+pub fn register_core(core: usize, limits: PoolLimits) -> Result<(), RegistryError> {
+    let mailboxes = MAILBOXES.get().ok_or(RegistryError::Uninitialized)?;
+    let cores = mailboxes.len();
+    if core >= cores {
+        return Err(RegistryError::OutOfRange { core, cores });
+    }
+    CORE.with(|local| {
+        if let Some(local) = local.get() {
+            return Err(RegistryError::AlreadyRegistered(local.id));
+        }
+        mailboxes[core]
+            .claimed
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .map_err(|_| RegistryError::CoreInUse(core))?;
+        // checked above that nothing is set yet
+        let _ = local.set(Core { id: core, pool: BufferPool::with_limits(limits) });
+        Ok(())
+    })
+}
+
+/// Core id of current thread, None if it is not registered.
+#[inline]
+pub fn current_core() -> Option<usize> {
+    CORE.with(|local| local.get().map(|local| local.id))
+}
+
+/// Stats of the pool of current core, None if current thread is not registered.
+#[inline]
+pub fn stats() -> Option<PoolStats> {
+    CORE.with(|local| local.get().map(|local| local.pool.stats()))
+}
+
+/// Move buffers returned by other cores into the pool of current core, return amount collected.
+fn collect(core: &Core) -> usize {
+    // registered thread implies initialized registry
+    let mailbox = &MAILBOXES.get().unwrap()[core.id];
+    if mailbox.pending.load(Ordering::Acquire) == 0 {
+        return 0;
+    }
+    let buffers = {
+        let mut buffers = mailbox.buffers.lock().unwrap_or_else(PoisonError::into_inner);
+        mailbox.pending.fetch_sub(buffers.len(), Ordering::Release);
+        std::mem::take(&mut *buffers)
+    };
+    let collected = buffers.len();
+    for Migrated(buffer) in buffers {
+        // # Safety
+        // buffer is taken from pool of this core
+        unsafe { core.pool.put(buffer) };
+    }
+    collected
+}
+
+/// Take buffer from the pool of current core, buffers returned by other cores are collected when the pool is empty.
+/// Slice of the buffer start at offset 0 with length 0.
+/// # Panics
+/// Current thread is not registered by [register_core].
+pub fn take() -> CoreBuffer {
+    CORE.with(|local| {
+        let core = local.get().expect("current thread is not registered as a core");
+        if core.pool.stats().retained == 0 {
+            // collected buffers may all be released with PoolLimits::max_retained
+            collect(core);
+        }
+        // # Safety
+        // ALIGN is power of two
+        let buffer = unsafe { core.pool.take() };
+        CoreBuffer { slice: ManuallyDrop::new(BufferSlice::new(buffer, 0, 0)), core: core.id }
+    })
+}
+
+/// Collect buffers returned by other cores then [BufferPool::trim] the pool of current core,
+/// meant to be called periodically by each worker. Return amount collected and released.
+pub fn maintain() -> (usize, usize) {
+    CORE.with(|local| match local.get() {
+        Some(core) => (collect(core), core.pool.trim()),
+        None => (0, 0),
+    })
+}
+
+/// Buffer taken by [take], can be moved to other threads together with its connection.
+///
+/// Dropped on the owner core it goes back to the pool directly, otherwise it is sent to mailbox of the owner
+/// and recycled by next [take] or [maintain] there.
+pub struct CoreBuffer {
+    slice: ManuallyDrop<BufferSlice<CORE_BUFFER_SIZE, ALIGN>>,
+    core: usize,
+}
+
+// # Safety
+// buffer is the only owner of its allocation, pool is only touched by the owner thread
+unsafe impl Send for CoreBuffer {}
+
+impl CoreBuffer {
+    /// Core that own this buffer.
+    #[inline(always)]
+    pub fn core(&self) -> usize {
+        self.core
+    }
+}
+
+impl Deref for CoreBuffer {
+    type Target = BufferSlice<CORE_BUFFER_SIZE, ALIGN>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.slice
+    }
+}
+
+impl DerefMut for CoreBuffer {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.slice
+    }
+}
+
+impl Debug for CoreBuffer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&*self.slice, f)
+    }
+}
+
+impl Drop for CoreBuffer {
+    fn drop(&mut self) {
+        // # Safety
+        // slice is not used after taken
+        let mut buffer = Some(unsafe { ManuallyDrop::take(&mut self.slice) }.into_inner());
+        // thread local may be destroyed already when thread is exiting
+        let _ = CORE.try_with(|local| {
+            if let Some(core) = local.get() {
+                if core.id == self.core {
+                    // # Safety
+                    // buffer is taken from pool of this core
+                    unsafe { core.pool.put(buffer.take().unwrap()) };
+                }
+            }
+        });
+        // without registry the buffer is released
+        if let (Some(buffer), Some(mailboxes)) = (buffer, MAILBOXES.get()) {
+            let mailbox = &mailboxes[self.core];
+            let mut buffers = mailbox.buffers.lock().unwrap_or_else(PoisonError::into_inner);
+            buffers.push(Migrated(buffer));
+            // counted under the lock so collect never see more buffers than pending
+            mailbox.pending.fetch_add(1, Ordering::Release);
+        }
+    }
+}
+
+unsafe impl IoBuf for CoreBuffer {
+    #[inline(always)]
+    fn read_ptr(&self) -> *const u8 {
+        self.slice.read_ptr()
+    }
+
+    #[inline(always)]
+    fn bytes_init(&self) -> usize {
+        self.slice.bytes_init()
+    }
+}
+
+unsafe impl IoBufMut for CoreBuffer {
+    #[inline(always)]
+    fn write_ptr(&mut self) -> *mut u8 {
+        self.slice.write_ptr()
+    }
+
+    #[inline(always)]
+    fn bytes_total(&mut self) -> usize {
+        self.slice.bytes_total()
+    }
+
+    #[inline(always)]
+    unsafe fn set_init(&mut self, pos: usize) {
+        self.slice.set_init(pos);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_registry() {
+        init(2).unwrap();
+        assert_eq!(init(2), Err(RegistryError::AlreadyInitialized(2)));
+        assert_eq!(register_core(2, PoolLimits::default()), Err(RegistryError::OutOfRange { core: 2, cores: 2 }));
+
+        let (to_other, from_owner) = mpsc::channel::<CoreBuffer>();
+        let (done, wait) = mpsc::channel::<()>();
+        let other = std::thread::spawn(move || {
+            register_core(1, PoolLimits::default()).unwrap();
+            let buffer = from_owner.recv().unwrap();
+            assert_eq!(buffer.core(), 0);
+            // dropped on core 1, sent to mailbox of core 0
+            drop(buffer);
+            assert_eq!(stats().unwrap().retained, 0);
+            done.send(()).unwrap();
+        });
+
+        register_core(0, PoolLimits::default()).unwrap();
+        assert_eq!(register_core(0, PoolLimits::default()), Err(RegistryError::AlreadyRegistered(0)));
+        let duplicated = std::thread::spawn(|| register_core(0, PoolLimits::default()));
+        assert_eq!(duplicated.join().unwrap(), Err(RegistryError::CoreInUse(0)));
+        assert_eq!(current_core(), Some(0));
+        let local = take();
+        let migrated = take();
+        let ptr = migrated.read_ptr();
+        drop(local);
+        assert_eq!(stats().unwrap().retained, 1);
+
+        to_other.send(migrated).unwrap();
+        wait.recv().unwrap();
+        other.join().unwrap();
+        assert_eq!(maintain(), (1, 0));
+        let stats = stats().unwrap();
+        assert_eq!((stats.retained, stats.outstanding), (2, 0));
+        // returned buffer is reused
+        assert!([take().read_ptr(), take().read_ptr()].contains(&ptr));
+
+        // core 1 is released by the exited thread, reuse it without retaining buffers
+        let (to_owner, from_other) = mpsc::channel::<CoreBuffer>();
+        let (returned, wait) = mpsc::channel::<()>();
+        let (done, finished) = mpsc::channel::<()>();
+        let unretained = std::thread::spawn(move || {
+            register_core(1, PoolLimits { max_retained: 0, ..PoolLimits::default() }).unwrap();
+            to_owner.send(take()).unwrap();
+            wait.recv().unwrap();
+            // collected buffer is released, a new one is allocated
+            let buffer = take();
+            assert_eq!(buffer.core(), 1);
+            let stats = super::stats().unwrap();
+            assert_eq!((stats.hits, stats.misses, stats.released, stats.retained), (0, 2, 1, 0));
+            finished.recv().unwrap();
+        });
+        // dropped on core 0, sent to mailbox of core 1
+        drop(from_other.recv().unwrap());
+        returned.send(()).unwrap();
+        done.send(()).unwrap();
+        unretained.join().unwrap();
+    }
+}
@@ -0,0 +1,244 @@
+use monoio::buf::{IoBuf, IoBufMut};
+use std::fmt::{Debug, Formatter};
+use std::ops::{Deref, DerefMut};
+
+use super::buffer_pool::{PoolLimits, PoolStats, Retained};
+use super::growable::GrowableBuffer;
+use super::{PaddedHaystack, ALIGN};
+
+/// Usable capacity of each size class: heads, bodies, large bodies and uploads.
+pub const SIZE_CLASSES: [usize; CLASS_COUNT] = [4 << 10, 16 << 10, 64 << 10, 1 << 20];
+pub const CLASS_COUNT: usize = 4;
+/// Zeroed bytes allocated after the capacity, see [super::PADDING].
+pub const PADDING: usize = super::PADDING;
+
+/// Smallest class that can hold `size` bytes, None if `size` is larger than the largest class.
+#[inline]
+pub const fn class_for(size: usize) -> Option<usize> {
+    let mut class = 0;
+    while class < CLASS_COUNT {
+        if size <= SIZE_CLASSES[class] {
+            return Some(class);
+        }
+        class += 1;
+    }
+    None
+}
+
+/// Buffer with capacity of one of [SIZE_CLASSES], aligned to [ALIGN] and followed by [PADDING] readable bytes.
+/// Allocation and padding are handled by [GrowableBuffer] which is never grown here.
+pub struct ClassBuffer {
+    buffer: GrowableBuffer<ALIGN>,
+    class: usize,
+}
+
+impl ClassBuffer {
+    /// Allocate zeroed buffer of `class` with length 0.
+    /// # Panics
+    /// `class` is not less than [CLASS_COUNT].
+    #[inline]
+    pub fn allocate(class: usize) -> Self {
+        Self { buffer: GrowableBuffer::with_capacity(SIZE_CLASSES[class]), class }
+    }
+
+    #[inline(always)]
+    pub fn class(&self) -> usize {
+        self.class
+    }
+
+    /// Usable capacity, [PADDING] is not included.
+    #[inline(always)]
+    pub fn capacity(&self) -> usize {
+        SIZE_CLASSES[self.class]
+    }
+
+    #[inline]
+    pub fn set_len(&mut self, len: usize) {
+        self.buffer.set_len(len);
+    }
+
+    /// # Safety
+    /// `len` must not be greater than capacity.
+    #[inline(always)]
+    pub unsafe fn set_len_unchecked(&mut self, len: usize) {
+        self.buffer.set_len_unchecked(len);
+    }
+
+    #[inline(always)]
+    pub fn ptr(&self) -> *mut u8 {
+        self.buffer.ptr()
+    }
+
+    /// Whole allocation including bytes after the data and [PADDING].
+    #[inline(always)]
+    pub fn padded(&self) -> &[u8] {
+        self.buffer.padded()
+    }
+}
+
+// # Safety
+// same layout as GrowableBuffer
+unsafe impl PaddedHaystack for ClassBuffer {
+    #[inline(always)]
+    fn data(&self) -> &[u8] {
+        self
+    }
+
+    #[inline(always)]
+    fn padded(&self) -> &[u8] {
+        self.buffer.padded()
+    }
+}
+
+impl Debug for ClassBuffer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClassBuffer")
+            .field("class", &self.class)
+            .field("len", &self.buffer.len())
+            .finish()
+    }
+}
+
+impl Deref for ClassBuffer {
+    type Target = [u8];
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.buffer
+    }
+}
+
+impl DerefMut for ClassBuffer {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.buffer
+    }
+}
+
+unsafe impl IoBuf for ClassBuffer {
+    #[inline(always)]
+    fn read_ptr(&self) -> *const u8 {
+        self.buffer.read_ptr()
+    }
+
+    #[inline(always)]
+    fn bytes_init(&self) -> usize {
+        self.buffer.bytes_init()
+    }
+}
+
+unsafe impl IoBufMut for ClassBuffer {
+    #[inline(always)]
+    fn write_ptr(&mut self) -> *mut u8 {
+        self.buffer.write_ptr()
+    }
+
+    #[inline(always)]
+    fn bytes_total(&mut self) -> usize {
+        self.buffer.bytes_total()
+    }
+
+    #[inline(always)]
+    unsafe fn set_init(&mut self, pos: usize) {
+        self.buffer.set_init(pos);
+    }
+}
+
+/// Pool of [ClassBuffer], each size class is retained and counted separately like [super::buffer_pool::BufferPool].
+pub struct SizeClassPool {
+    classes: [Retained<ClassBuffer>; CLASS_COUNT],
+    // !Send
+    _marker: std::marker::PhantomData<*mut ()>,
+}
+
+impl SizeClassPool {
+    pub const fn default() -> Self {
+        Self::with_limits([PoolLimits::default(); CLASS_COUNT])
+    }
+
+    pub const fn with_limits(limits: [PoolLimits; CLASS_COUNT]) -> Self {
+        let mut classes = [const { Retained::new(PoolLimits::default()) }; CLASS_COUNT];
+        let mut class = 0;
+        while class < CLASS_COUNT {
+            classes[class].limits = limits[class];
+            class += 1;
+        }
+        Self { classes, _marker: std::marker::PhantomData }
+    }
+
+    #[inline]
+    pub fn limits(&self, class: usize) -> &PoolLimits {
+        self.classes[class].limits()
+    }
+
+    #[inline]
+    pub fn stats(&self, class: usize) -> PoolStats {
+        self.classes[class].stats()
+    }
+
+    /// Allocate buffers of `class` until `amount` are retained, capped by [PoolLimits::max_retained].
+    #[inline]
+    pub fn pre_allocate(&self, class: usize, amount: usize) {
+        self.classes[class].pre_allocate(amount, || ClassBuffer::allocate(class));
+    }
+
+    /// Take buffer of the smallest class that can hold `size` bytes with length 0,
+    /// allocate new one if that class is empty. None if `size` is larger than the largest class.
+    #[inline]
+    pub fn take(&self, size: usize) -> Option<ClassBuffer> {
+        let class = class_for(size)?;
+        Some(self.classes[class].take(|| ClassBuffer::allocate(class)))
+    }
+
+    /// Return buffer to its class, it is released instead when [PoolLimits::max_retained] buffers are retained.
+    #[inline]
+    pub fn put(&self, mut buffer: ClassBuffer) {
+        buffer.buffer.clear();
+        self.classes[buffer.class].put(buffer);
+    }
+
+    /// [super::buffer_pool::BufferPool::trim] every class, return amount released.
+    pub fn trim(&self) -> usize {
+        self.classes.iter().map(Retained::trim).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_class_pool() {
+        assert_eq!(class_for(0), Some(0));
+        assert_eq!(class_for(4096), Some(0));
+        assert_eq!(class_for(4097), Some(1));
+        assert_eq!(class_for(1 << 20), Some(3));
+        assert_eq!(class_for((1 << 20) + 1), None);
+
+        let mut limits = [PoolLimits::default(); CLASS_COUNT];
+        limits[2] = PoolLimits { max_retained: 1, high_watermark: 0, low_watermark: 0 };
+        let pool = SizeClassPool::with_limits(limits);
+        assert!(pool.take((1 << 20) + 1).is_none());
+
+        let mut buffer = pool.take(10_000).unwrap();
+        assert_eq!((buffer.class(), buffer.capacity(), buffer.len()), (1, 16 << 10, 0));
+        assert!(buffer.ptr().is_aligned_to(ALIGN));
+        assert_eq!(buffer.padded().len(), (16 << 10) + PADDING);
+        assert!(buffer.padded().iter().all(|&byte| byte == 0));
+        buffer.set_len(3);
+        buffer.copy_from_slice(b"abc");
+        let ptr = buffer.ptr();
+        pool.put(buffer);
+
+        let buffer = pool.take(5000).unwrap();
+        assert_eq!((buffer.ptr(), buffer.len()), (ptr, 0));
+        assert_eq!((pool.stats(1).hits, pool.stats(1).misses), (1, 1));
+
+        let taken = [pool.take(20_000).unwrap(), pool.take(20_000).unwrap()];
+        taken.into_iter().for_each(|buffer| pool.put(buffer));
+        let stats = pool.stats(2);
+        assert_eq!((stats.retained, stats.released, stats.peak_outstanding), (1, 1, 2));
+        assert_eq!(pool.trim(), 1);
+        assert_eq!(pool.stats(2).retained, 0);
+    }
+}
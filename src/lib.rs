@@ -6,13 +6,15 @@
 #![feature(core_intrinsics)]
 #![feature(const_eval_select)]
 #![feature(const_pointer_is_aligned)]
-#![feature(stdarch_x86_avx512)]
+#![cfg_attr(target_arch = "x86_64", feature(stdarch_x86_avx512))]
+#![feature(avx512_target_feature)]
 #![feature(strict_provenance)]
 #![feature(const_mut_refs)]
 
 pub mod buffer;
-mod parser;
+pub mod parser;
+pub mod search;
 pub mod utils;
-mod parts;
+pub mod parts;
 pub mod limit;
 pub mod offset;
\ No newline at end of file
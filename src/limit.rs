@@ -2,4 +2,42 @@ pub const MAX_METHOD_LENGTH: usize = 8;
 pub const MAX_VERSION_LENGTH: usize = 8;
 pub const MAX_PATH_LENGTH: usize = 4096 - 64;// start at 32, + 2 for length prefix
 pub const MAX_HEADER_LENGTH: usize = 32;
-//pub const MAX_HEADER_VALUE_LENGTH: usize = ?; : 16bit length prefix
\ No newline at end of file
+// 16bit length prefix
+pub const MAX_HEADER_VALUE_LENGTH: usize = u16::MAX as usize;
+pub const MAX_HEADER_COUNT: usize = 100;
+// request line + headers + empty line
+pub const MAX_HEAD_LENGTH: usize = 8192;
+
+/// Runtime limits for request parser, default values are taken from constants in this module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    pub max_method_length: usize,
+    pub max_path_length: usize,
+    /// Values above [MAX_HEADER_LENGTH] are capped, header key can't store longer name.
+    pub max_header_name_length: usize,
+    pub max_header_value_length: usize,
+    pub max_header_count: usize,
+    pub max_head_length: usize,
+}
+
+impl Limits {
+    pub const fn default() -> Self {
+        Self {
+            max_method_length: MAX_METHOD_LENGTH,
+            max_path_length: MAX_PATH_LENGTH,
+            max_header_name_length: MAX_HEADER_LENGTH,
+            max_header_value_length: MAX_HEADER_VALUE_LENGTH,
+            max_header_count: MAX_HEADER_COUNT,
+            max_head_length: MAX_HEAD_LENGTH,
+        }
+    }
+
+    #[inline]
+    pub(crate) const fn header_name_length(&self) -> usize {
+        if self.max_header_name_length < MAX_HEADER_LENGTH {
+            self.max_header_name_length
+        } else {
+            MAX_HEADER_LENGTH
+        }
+    }
+}
@@ -1,8 +1,11 @@
 pub const HEADER_COUNT_OFFSET: usize = 8; // u16
 pub const HEADER_LENGTH_OFFSET: usize = 10; // u16
+pub const PATH_LENGTH_OFFSET: usize = 12; // u16
 pub const METHOD_OFFSET: usize = 16;
 pub const VERSION_OFFSET: usize = 24;
 // start at 4096 due possible overlap during copy
 pub const PATH_OFFSET: usize = 4096;
+// header keys are stored as 32 bytes aligned key until PATH_OFFSET
 pub const HEADER_OFFSET: usize = 32;
-// header value has 16bit length prefix, start from PATH_OFFSET+PATH_LENGTH+ 64 bytes padding
\ No newline at end of file
+// header value has 16bit length prefix, start from PATH_OFFSET+PATH_LENGTH+ 64 bytes padding
+pub const HEADER_VALUE_PADDING: usize = 64;
@@ -0,0 +1,119 @@
+use std::fmt::{Display, Formatter};
+
+/// Part of request that parser was processing when error occurred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Phase {
+    Method,
+    Path,
+    Version,
+    HeaderName,
+    HeaderValue,
+}
+
+impl Phase {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Phase::Method => "method",
+            Phase::Path => "path",
+            Phase::Version => "version",
+            Phase::HeaderName => "header name",
+            Phase::HeaderValue => "header value",
+        }
+    }
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// Request ended before head is complete.
+    Incomplete,
+    /// Required value is empty.
+    Empty,
+    /// Value is longer than allowed.
+    TooLong,
+    /// Byte is not allowed in this part of request.
+    InvalidToken,
+    /// Line is terminated by LF without CR.
+    BareLf,
+    /// Header line doesn't contain `:`.
+    MissingColon,
+    /// Header line start with whitespace (obsolete line folding).
+    ObsFold,
+    /// Amount of headers exceed the limit.
+    TooManyHeaders,
+    /// Request line and headers are longer than the limit.
+    HeadTooLarge,
+    /// `Content-Length` is repeated with different values or sent together with `Transfer-Encoding`,
+    /// message length would be ambiguous.
+    ConflictingFraming,
+}
+
+impl ErrorKind {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            ErrorKind::Incomplete => "incomplete request",
+            ErrorKind::Empty => "empty value",
+            ErrorKind::TooLong => "value too long",
+            ErrorKind::InvalidToken => "invalid token",
+            ErrorKind::BareLf => "bare LF",
+            ErrorKind::MissingColon => "missing colon",
+            ErrorKind::ObsFold => "obsolete line folding",
+            ErrorKind::TooManyHeaders => "too many headers",
+            ErrorKind::HeadTooLarge => "head too large",
+            ErrorKind::ConflictingFraming => "conflicting framing headers",
+        }
+    }
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("{kind} in {phase} at byte {offset}")]
+pub struct ParseError {
+    kind: ErrorKind,
+    phase: Phase,
+    offset: usize,
+}
+
+impl ParseError {
+    #[inline]
+    pub const fn new(kind: ErrorKind, phase: Phase, offset: usize) -> Self {
+        Self { kind, phase, offset }
+    }
+
+    #[inline]
+    pub const fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    #[inline]
+    pub const fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// Offset from start of the buffer where error was detected.
+    #[inline]
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Suggested response status code for this error.
+    pub const fn status_code(&self) -> u16 {
+        match (self.kind, self.phase) {
+            // no method that long is implemented
+            (ErrorKind::TooLong, Phase::Method) => 501,
+            (ErrorKind::TooLong, Phase::Path) | (ErrorKind::HeadTooLarge, Phase::Method | Phase::Path | Phase::Version) => 414,
+            (ErrorKind::TooLong, Phase::HeaderName | Phase::HeaderValue) | (ErrorKind::TooManyHeaders | ErrorKind::HeadTooLarge, _) => 431,
+            _ => 400,
+        }
+    }
+}
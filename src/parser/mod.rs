@@ -0,0 +1,629 @@
+use std::ops::Range;
+use std::simd::cmp::SimdPartialEq;
+use std::simd::Simd;
+
+use crate::buffer::BufferSlice;
+use crate::limit::{Limits, MAX_HEADER_LENGTH};
+use crate::parts::header::{AlignedHeaderKey, GetAll, HeaderMap};
+use crate::utils::ascii::first_invalid_tchar;
+use crate::utils::simd::avx_mask_true;
+use crate::utils::simd::validate::{first_invalid_field_value, first_invalid_request_target};
+use crate::utils::dispatch::isa;
+#[cfg(target_arch = "x86_64")]
+use crate::utils::dispatch::Isa;
+
+pub use crate::utils::simd::validate::Strictness;
+pub use error::{ErrorKind, ParseError, Phase};
+
+mod error;
+
+const PROCESS_SIZE: usize = 64;
+const HTTP_VERSION_LENGTH: usize = b"HTTP/1.1".len();
+
+/// Range of header name and value
+type FieldRange = (Range<usize>, Range<usize>);
+
+/// Parsed request head, all values are borrowed from the source buffer.
+pub struct Request<'buf> {
+    method: &'buf [u8],
+    path: &'buf [u8],
+    version: &'buf [u8],
+    headers: HeaderMap<'buf>,
+}
+
+impl<'buf> Request<'buf> {
+    #[inline]
+    pub fn method(&self) -> &'buf [u8] {
+        self.method
+    }
+
+    #[inline]
+    pub fn path(&self) -> &'buf [u8] {
+        self.path
+    }
+
+    #[inline]
+    pub fn version(&self) -> &'buf [u8] {
+        self.version
+    }
+
+    #[inline]
+    pub fn headers(&self) -> &HeaderMap<'buf> {
+        &self.headers
+    }
+
+    /// Lookup first value of header by case-insensitive name.
+    #[inline]
+    pub fn header(&self, name: &[u8]) -> Option<&'buf [u8]> {
+        if name.len() > MAX_HEADER_LENGTH {
+            return None;
+        }
+        self.headers.get(&AlignedHeaderKey::new(name))
+    }
+
+    /// Every value of header by case-insensitive name in order of appearance.
+    #[inline]
+    pub fn header_all(&self, name: &[u8]) -> GetAll<'_, 'buf> {
+        // longer name can't be stored, lookup a key that never match
+        let key = if name.len() > MAX_HEADER_LENGTH { AlignedHeaderKey::default() } else { AlignedHeaderKey::new(name) };
+        self.headers.get_all(&key)
+    }
+}
+
+/// Load 64 bytes block start at `pos` and return bitmask of `byte`,
+/// bits after end of `bytes` are always unset.
+#[inline(always)]
+fn block_mask(bytes: &[u8], pos: usize, byte: u8) -> u64 {
+    let needle = Simd::splat(byte);
+    match bytes.get(pos..pos + PROCESS_SIZE) {
+        Some(block) => Simd::<u8, PROCESS_SIZE>::from_slice(block).simd_eq(needle).to_bitmask(),
+        None => {
+            let remain = bytes.len() - pos;
+            let mask = Simd::<u8, PROCESS_SIZE>::load_or_default(&bytes[pos..]).simd_eq(needle).to_bitmask();
+            mask & avx_mask_true!(remain)
+        }
+    }
+}
+
+/// Find first `byte` in `bytes[from..to]`.
+#[inline(always)]
+fn find_byte(bytes: &[u8], from: usize, to: usize, byte: u8) -> Option<usize> {
+    let bytes = &bytes[..to];
+    let mut pos = from;
+    while pos < to {
+        let mask = block_mask(bytes, pos, byte);
+        if mask != 0 {
+            return Some(pos + mask.trailing_zeros() as usize);
+        }
+        pos += PROCESS_SIZE;
+    }
+    None
+}
+
+/// Find last `byte` in `bytes[from..to]`.
+#[inline(always)]
+fn rfind_byte(bytes: &[u8], from: usize, to: usize, byte: u8) -> Option<usize> {
+    let bytes = &bytes[from..];
+    let mut end = to - from;
+    while end > 0 {
+        let pos = end.saturating_sub(PROCESS_SIZE);
+        let mask = block_mask(&bytes[..end], pos, byte);
+        if mask != 0 {
+            return Some(from + pos + (63 - mask.leading_zeros() as usize));
+        }
+        end = pos;
+    }
+    None
+}
+
+#[inline(always)]
+fn is_whitespace(byte: u8) -> bool {
+    byte == b' ' || byte == b'\t'
+}
+
+/// Parse request line `bytes[..cr]`, return range of method, path and version.
+#[inline(always)]
+fn parse_request_line(bytes: &[u8], cr: usize, limits: &Limits, strictness: Strictness) -> Result<[Range<usize>; 3], ParseError> {
+    let method_end = find_byte(bytes, 0, cr, b' ').ok_or(ParseError::new(ErrorKind::InvalidToken, Phase::Method, cr))?;
+    if method_end == 0 {
+        return Err(ParseError::new(ErrorKind::Empty, Phase::Method, 0));
+    }
+    if method_end > limits.max_method_length {
+        return Err(ParseError::new(ErrorKind::TooLong, Phase::Method, limits.max_method_length));
+    }
+    if let Some(invalid) = first_invalid_tchar(&bytes[..method_end]) {
+        return Err(ParseError::new(ErrorKind::InvalidToken, Phase::Method, invalid));
+    }
+    let version_start = rfind_byte(bytes, method_end, cr, b' ').unwrap_or(method_end) + 1;
+    // path must contain at least 1 byte
+    if version_start <= method_end + 2 {
+        return Err(ParseError::new(ErrorKind::Empty, Phase::Path, method_end + 1));
+    }
+    let path_start = method_end + 1;
+    if version_start - 1 - path_start > limits.max_path_length {
+        return Err(ParseError::new(ErrorKind::TooLong, Phase::Path, path_start + limits.max_path_length));
+    }
+    if let Some(invalid) = first_invalid_request_target(&bytes[path_start..version_start - 1], strictness) {
+        return Err(ParseError::new(ErrorKind::InvalidToken, Phase::Path, path_start + invalid));
+    }
+    // HTTP-version = "HTTP/" DIGIT "." DIGIT
+    let version = &bytes[version_start..cr];
+    let valid_version = matches!(version, [b'H', b'T', b'T', b'P', b'/', major, b'.', minor] if major.is_ascii_digit() && minor.is_ascii_digit());
+    if version.len() != HTTP_VERSION_LENGTH || !valid_version {
+        return Err(ParseError::new(ErrorKind::InvalidToken, Phase::Version, version_start));
+    }
+    Ok([0..method_end, path_start..version_start - 1, version_start..cr])
+}
+
+/// Parse header line `bytes[from..cr]`, return range of name and value.
+#[inline(always)]
+fn parse_header_line(bytes: &[u8], from: usize, cr: usize, limits: &Limits, strictness: Strictness) -> Result<FieldRange, ParseError> {
+    if is_whitespace(bytes[from]) {
+        return Err(ParseError::new(ErrorKind::ObsFold, Phase::HeaderName, from));
+    }
+    let colon = find_byte(bytes, from, cr, b':').ok_or(ParseError::new(ErrorKind::MissingColon, Phase::HeaderName, from))?;
+    let name_len = colon - from;
+    if name_len == 0 {
+        return Err(ParseError::new(ErrorKind::Empty, Phase::HeaderName, from));
+    }
+    let max_name_length = limits.header_name_length();
+    if name_len > max_name_length {
+        return Err(ParseError::new(ErrorKind::TooLong, Phase::HeaderName, from + max_name_length));
+    }
+    // whitespace between name and colon, control and non-ASCII bytes are rejected
+    if let Some(invalid) = first_invalid_tchar(&bytes[from..colon]) {
+        return Err(ParseError::new(ErrorKind::InvalidToken, Phase::HeaderName, from + invalid));
+    }
+    let mut value_start = colon + 1;
+    let mut value_end = cr;
+    while value_start < value_end && is_whitespace(bytes[value_start]) {
+        value_start += 1;
+    }
+    while value_end > value_start && is_whitespace(bytes[value_end - 1]) {
+        value_end -= 1;
+    }
+    if value_end - value_start > limits.max_header_value_length {
+        return Err(ParseError::new(ErrorKind::TooLong, Phase::HeaderValue, value_start + limits.max_header_value_length));
+    }
+    if let Some(invalid) = first_invalid_field_value(&bytes[value_start..value_end], strictness) {
+        return Err(ParseError::new(ErrorKind::InvalidToken, Phase::HeaderValue, value_start + invalid));
+    }
+    Ok((from..colon, value_start..value_end))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// Head is not complete yet, at least `needed_hint` more bytes are required.
+    Partial { needed_hint: usize },
+    /// Head is complete, value is amount of bytes consumed including empty line at the end of head.
+    Complete(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    RequestLine,
+    Headers,
+    Complete,
+}
+
+impl State {
+    /// Phase that parser will report when line can't be terminated.
+    #[inline]
+    const fn line_phase(&self) -> Phase {
+        match self {
+            State::RequestLine => Phase::Version,
+            _ => Phase::HeaderValue,
+        }
+    }
+}
+
+/// Resumable request head parser.
+///
+/// Parser only keep offsets of parsed values, so same [BufferSlice] can be fed again
+/// after more data has been read into it, bytes that already validated will not be scanned again.
+pub struct RequestParser {
+    limits: Limits,
+    strictness: Strictness,
+    state: State,
+    /// start of current line
+    line_start: usize,
+    /// bytes before this index doesn't contain end of current line
+    scanned: usize,
+    request_line: [Range<usize>; 3],
+    headers: Vec<FieldRange>,
+    /// value of first `Content-Length`
+    content_length: Option<Range<usize>>,
+    transfer_encoding: bool,
+}
+
+impl Default for RequestParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RequestParser {
+    pub const fn new() -> Self {
+        Self::with_limits(Limits::default())
+    }
+
+    pub const fn with_limits(limits: Limits) -> Self {
+        Self {
+            limits,
+            strictness: Strictness::Standard,
+            state: State::RequestLine,
+            line_start: 0,
+            scanned: 0,
+            request_line: [0..0, 0..0, 0..0],
+            headers: Vec::new(),
+            content_length: None,
+            transfer_encoding: false,
+        }
+    }
+
+    /// Reset state to parse new request, allocated memory will be reused.
+    pub fn reset(&mut self) {
+        self.state = State::RequestLine;
+        self.line_start = 0;
+        self.scanned = 0;
+        self.headers.clear();
+        self.content_length = None;
+        self.transfer_encoding = false;
+    }
+
+    /// Set how header values and request target are validated, default is [Strictness::Standard].
+    pub const fn with_strictness(mut self, strictness: Strictness) -> Self {
+        self.strictness = strictness;
+        self
+    }
+
+    #[inline]
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    #[inline]
+    pub fn strictness(&self) -> Strictness {
+        self.strictness
+    }
+
+    /// Range of request target inside parsed bytes, None if request line is not parsed yet.
+    /// It can be decoded in place with [BufferSlice::decode_in_place] once [Request] views are dropped.
+    #[inline]
+    pub fn path_range(&self) -> Option<Range<usize>> {
+        (self.state != State::RequestLine).then(|| self.request_line[1].clone())
+    }
+
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.state == State::Complete
+    }
+
+    /// Continue parsing from previous call, `request` must contain the same bytes as previous call
+    /// with optionally more bytes at the end.
+    pub fn parse<const LEN: usize, const ALIGN: usize>(&mut self, request: &BufferSlice<LEN, ALIGN>) -> Result<Status, ParseError> {
+        let bytes: &[u8] = request;
+        // # Safety
+        // instruction set is supported by current CPU
+        match isa() {
+            #[cfg(target_arch = "x86_64")]
+            Isa::Avx512 => unsafe { self.parse_avx512(bytes) },
+            #[cfg(target_arch = "x86_64")]
+            Isa::Avx2 => unsafe { self.parse_avx2(bytes) },
+            #[cfg(target_arch = "x86_64")]
+            Isa::X86V2 => unsafe { self.parse_x86v2(bytes) },
+            _ => self.parse_inner(bytes),
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[target_feature(enable = "avx512f,avx512bw")]
+    unsafe fn parse_avx512(&mut self, bytes: &[u8]) -> Result<Status, ParseError> {
+        self.parse_inner(bytes)
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[target_feature(enable = "avx2")]
+    unsafe fn parse_avx2(&mut self, bytes: &[u8]) -> Result<Status, ParseError> {
+        self.parse_inner(bytes)
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[target_feature(enable = "sse4.2,popcnt")]
+    unsafe fn parse_x86v2(&mut self, bytes: &[u8]) -> Result<Status, ParseError> {
+        self.parse_inner(bytes)
+    }
+
+    /// Parser body, always inlined into wrapper compiled for specific instruction set.
+    #[inline(always)]
+    fn parse_inner(&mut self, bytes: &[u8]) -> Result<Status, ParseError> {
+        let max_head_length = self.limits.max_head_length;
+        while self.state != State::Complete {
+            let Some(cr) = self.next_line(bytes)? else {
+                let needed_hint = self.needed_hint(bytes);
+                if bytes.len() + needed_hint > max_head_length {
+                    return Err(ParseError::new(ErrorKind::HeadTooLarge, self.state.line_phase(), max_head_length));
+                }
+                return Ok(Status::Partial { needed_hint });
+            };
+            if cr + 2 > max_head_length {
+                return Err(ParseError::new(ErrorKind::HeadTooLarge, self.state.line_phase(), max_head_length));
+            }
+            match self.state {
+                State::RequestLine => {
+                    self.request_line = parse_request_line(bytes, cr, &self.limits, self.strictness)?;
+                    self.state = State::Headers;
+                }
+                State::Headers if cr == self.line_start => self.state = State::Complete,
+                State::Headers => {
+                    if self.headers.len() == self.limits.max_header_count {
+                        return Err(ParseError::new(ErrorKind::TooManyHeaders, Phase::HeaderName, self.line_start));
+                    }
+                    let field = parse_header_line(bytes, self.line_start, cr, &self.limits, self.strictness)?;
+                    self.check_framing(bytes, &field)?;
+                    self.headers.push(field);
+                }
+                State::Complete => unreachable!(),
+            }
+            self.line_start = cr + 2;
+            self.scanned = self.line_start;
+        }
+        Ok(Status::Complete(self.line_start))
+    }
+
+    /// Build [Request] view over `request`, return None if head is not complete yet.
+    pub fn request<'buf, const LEN: usize, const ALIGN: usize>(&self, request: &'buf BufferSlice<LEN, ALIGN>) -> Option<Request<'buf>> {
+        if !self.is_complete() {
+            return None;
+        }
+        let bytes: &'buf [u8] = request;
+        let [method, path, version] = self.request_line.clone();
+        let mut headers = HeaderMap::with_capacity(self.headers.len());
+        for (name, value) in &self.headers {
+            headers.insert(AlignedHeaderKey::new(&bytes[name.clone()]), &bytes[value.clone()]);
+        }
+        Some(Request {
+            method: &bytes[method],
+            path: &bytes[path],
+            version: &bytes[version],
+            headers,
+        })
+    }
+
+    /// Reject `Content-Length` with different values or together with `Transfer-Encoding` (RFC 9112 section 6.3),
+    /// either would let another hop frame the message differently.
+    #[inline(always)]
+    fn check_framing(&mut self, bytes: &[u8], (name, value): &FieldRange) -> Result<(), ParseError> {
+        let name_bytes = &bytes[name.clone()];
+        let conflict = if name_bytes.eq_ignore_ascii_case(b"content-length") {
+            let conflict = match &self.content_length {
+                Some(first) => bytes[first.clone()] != bytes[value.clone()],
+                None => false,
+            };
+            self.content_length.get_or_insert(value.clone());
+            conflict || self.transfer_encoding
+        } else if name_bytes.eq_ignore_ascii_case(b"transfer-encoding") {
+            self.transfer_encoding = true;
+            self.content_length.is_some()
+        } else {
+            false
+        };
+        if conflict {
+            return Err(ParseError::new(ErrorKind::ConflictingFraming, Phase::HeaderName, name.start));
+        }
+        Ok(())
+    }
+
+    /// Find end of current line, return index of CR.
+    #[inline(always)]
+    fn next_line(&mut self, bytes: &[u8]) -> Result<Option<usize>, ParseError> {
+        let Some(lf) = find_byte(bytes, self.scanned, bytes.len(), b'\n') else {
+            self.scanned = bytes.len();
+            return Ok(None);
+        };
+        if lf == self.line_start || bytes[lf - 1] != b'\r' {
+            return Err(ParseError::new(ErrorKind::BareLf, self.state.line_phase(), lf));
+        }
+        Ok(Some(lf - 1))
+    }
+
+    /// Minimum amount of bytes to complete the head, assume every line after current line is empty.
+    #[inline(always)]
+    fn needed_hint(&self, bytes: &[u8]) -> usize {
+        let line = bytes.get(self.line_start..).unwrap_or_default();
+        match (self.state, line) {
+            (State::Headers, []) => 2,
+            (State::Headers, [b'\r']) => 1,
+            (_, [.., b'\r']) => 3,
+            _ => 4,
+        }
+    }
+}
+
+/// Parse request line and headers of HTTP/1.x request with default [Limits], incomplete request will be rejected.
+/// # Return
+/// Parsed [Request] and amount of bytes consumed including empty line at the end of head.
+#[inline]
+pub fn parse_request<const LEN: usize, const ALIGN: usize>(request: &BufferSlice<LEN, ALIGN>) -> Result<(Request<'_>, usize), ParseError> {
+    parse_request_with_limits(request, Limits::default())
+}
+
+/// Same as [parse_request] but enforce custom [Limits].
+pub fn parse_request_with_limits<const LEN: usize, const ALIGN: usize>(request: &BufferSlice<LEN, ALIGN>, limits: Limits) -> Result<(Request<'_>, usize), ParseError> {
+    let mut parser = RequestParser::with_limits(limits);
+    match parser.parse(request)? {
+        Status::Complete(consumed) => Ok((parser.request(request).unwrap(), consumed)),
+        Status::Partial { .. } => Err(ParseError::new(ErrorKind::Incomplete, parser.state.line_phase(), request.len())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REQUEST: &[u8] = b"GET /index.html HTTP/1.1\r\nHost: developer.mozilla.org\r\nAccept-Language: fr\r\nUser-Agent: curl/7.64.1\r\nAccept:*/* \r\n\r\nbody";
+
+    #[test]
+    fn test_parse_request() {
+        let buffer = BufferSlice::<8192, 4096>::from_slice(REQUEST);
+        let (request, consumed) = parse_request(&buffer).unwrap();
+        assert_eq!(request.method(), b"GET");
+        assert_eq!(request.path(), b"/index.html");
+        assert_eq!(request.version(), b"HTTP/1.1");
+        assert_eq!(request.headers().len(), 4);
+        assert_eq!(request.header(b"host"), Some(b"developer.mozilla.org".as_slice()));
+        assert_eq!(request.header(b"USER-AGENT"), Some(b"curl/7.64.1".as_slice()));
+        assert_eq!(request.header(b"Accept"), Some(b"*/*".as_slice()));
+        assert_eq!(&REQUEST[consumed..], b"body");
+    }
+
+    #[test]
+    fn test_long_request_line() {
+        let path = "/a".repeat(100);
+        let raw = format!("POST {path} HTTP/1.0\r\nX-Long: {}\r\n\r\n", "v".repeat(200));
+        let buffer = BufferSlice::<8192, 4096>::from_slice(raw.as_bytes());
+        let (request, consumed) = parse_request(&buffer).unwrap();
+        assert_eq!(request.method(), b"POST");
+        assert_eq!(request.path(), path.as_bytes());
+        assert_eq!(request.version(), b"HTTP/1.0");
+        assert_eq!(request.header(b"x-long").map(<[u8]>::len), Some(200));
+        assert_eq!(consumed, raw.len());
+    }
+
+    #[test]
+    fn test_invalid_request() {
+        let parse = |raw: &[u8]| parse_request(&BufferSlice::<4096, 4096>::from_slice(raw))
+            .map(|(_, consumed)| consumed)
+            .map_err(|error| (error.kind(), error.phase(), error.offset()));
+        assert_eq!(parse(b"GET / HTTP/1.1\r\nHost: a\r\n"), Err((ErrorKind::Incomplete, Phase::HeaderValue, 25)));
+        assert_eq!(parse(b" / HTTP/1.1\r\n\r\n"), Err((ErrorKind::Empty, Phase::Method, 0)));
+        assert_eq!(parse(b"GET HTTP/1.1\r\n\r\n"), Err((ErrorKind::Empty, Phase::Path, 4)));
+        assert_eq!(parse(b"GET  HTTP/1.1\r\n\r\n"), Err((ErrorKind::Empty, Phase::Path, 4)));
+        assert_eq!(parse(b"GET / HTTP/1\r\n\r\n"), Err((ErrorKind::InvalidToken, Phase::Version, 6)));
+        assert_eq!(parse(b"GET / HTTP/1.1\r\nHost : a\r\n\r\n"), Err((ErrorKind::InvalidToken, Phase::HeaderName, 20)));
+        assert_eq!(parse(b"GET / HTTP/1.1\r\nHo\x00st: a\r\n\r\n"), Err((ErrorKind::InvalidToken, Phase::HeaderName, 18)));
+        assert_eq!(parse(b"GET / HTTP/1.1\r\nX-\xc3\xa9: a\r\n\r\n"), Err((ErrorKind::InvalidToken, Phase::HeaderName, 18)));
+        assert_eq!(parse(b"GET / HTTP/1.1\r\nTransfer Encoding: chunked\r\n\r\n"), Err((ErrorKind::InvalidToken, Phase::HeaderName, 24)));
+        assert_eq!(parse(b"GET /a b HTTP/1.1\r\n\r\n"), Err((ErrorKind::InvalidToken, Phase::Path, 6)));
+        assert_eq!(parse(b"GET /a\x00 HTTP/1.1\r\n\r\n"), Err((ErrorKind::InvalidToken, Phase::Path, 6)));
+        assert_eq!(parse(b"GET / HTTP/1.1\r\nHost: a\x00b\r\n\r\n"), Err((ErrorKind::InvalidToken, Phase::HeaderValue, 23)));
+        assert_eq!(parse(b"GET / HTTP/1.1\r\nHost: a\rX: b\r\n\r\n"), Err((ErrorKind::InvalidToken, Phase::HeaderValue, 23)));
+        assert_eq!(parse(b"GET / HTTP/1.1\r\nHost\r\n\r\n"), Err((ErrorKind::MissingColon, Phase::HeaderName, 16)));
+        assert_eq!(parse(b"GET / HTTP/1.1\r\n: a\r\n\r\n"), Err((ErrorKind::Empty, Phase::HeaderName, 16)));
+        assert_eq!(parse(b"GET / HTTP/1.1\r\nHost: a\r\n b\r\n\r\n"), Err((ErrorKind::ObsFold, Phase::HeaderName, 25)));
+        assert_eq!(parse(b"GET / HTTP/1.1\nHost: a\r\n\r\n"), Err((ErrorKind::BareLf, Phase::Version, 14)));
+        assert_eq!(parse(b"GET / HTTP/1.1\r\nHost: a\n\r\n"), Err((ErrorKind::BareLf, Phase::HeaderValue, 23)));
+        assert_eq!(parse(b"GE\x00T / HTTP/1.1\r\n\r\n"), Err((ErrorKind::InvalidToken, Phase::Method, 2)));
+        assert_eq!(parse(b"G(T / HTTP/1.1\r\n\r\n"), Err((ErrorKind::InvalidToken, Phase::Method, 1)));
+        assert_eq!(parse(b"GET / HTTP/x.y\r\n\r\n"), Err((ErrorKind::InvalidToken, Phase::Version, 6)));
+        assert_eq!(parse(b"GET / HTTP/1-1\r\n\r\n"), Err((ErrorKind::InvalidToken, Phase::Version, 6)));
+        assert_eq!(parse(b"GET / HTTPS1.1\r\n\r\n"), Err((ErrorKind::InvalidToken, Phase::Version, 6)));
+
+        let name = "a".repeat(MAX_HEADER_LENGTH + 1);
+        let error = parse_request(&BufferSlice::<4096, 4096>::from_slice(format!("GET / HTTP/1.1\r\n{name}: a\r\n\r\n").as_bytes())).err().unwrap();
+        assert_eq!((error.kind(), error.phase(), error.offset()), (ErrorKind::TooLong, Phase::HeaderName, 16 + MAX_HEADER_LENGTH));
+        assert_eq!(error.status_code(), 431);
+    }
+
+    #[test]
+    fn test_limits() {
+        let limits = Limits {
+            max_method_length: 4,
+            max_path_length: 8,
+            max_header_name_length: 6,
+            max_header_value_length: 4,
+            max_header_count: 2,
+            max_head_length: 64,
+        };
+        let parse = |raw: &[u8]| parse_request_with_limits(&BufferSlice::<4096, 4096>::from_slice(raw), limits)
+            .map(|(_, consumed)| consumed)
+            .map_err(|error| (error.kind(), error.phase(), error.offset(), error.status_code()));
+        assert_eq!(parse(b"POST /1234567 HTTP/1.1\r\nAccept: *\r\nHost: a\r\n\r\n"), Ok(46));
+        assert_eq!(parse(b"DELETE / HTTP/1.1\r\n\r\n"), Err((ErrorKind::TooLong, Phase::Method, 4, 501)));
+        assert_eq!(parse(b"GET /123456789 HTTP/1.1\r\n\r\n"), Err((ErrorKind::TooLong, Phase::Path, 12, 414)));
+        assert_eq!(parse(b"GET / HTTP/1.1\r\nX-Large: a\r\n\r\n"), Err((ErrorKind::TooLong, Phase::HeaderName, 22, 431)));
+        assert_eq!(parse(b"GET / HTTP/1.1\r\nHost: abcde\r\n\r\n"), Err((ErrorKind::TooLong, Phase::HeaderValue, 26, 431)));
+        assert_eq!(parse(b"GET / HTTP/1.1\r\nA: 1\r\nB: 2\r\nC: 3\r\n\r\n"), Err((ErrorKind::TooManyHeaders, Phase::HeaderName, 28, 431)));
+        assert_eq!(parse(&[b"GET / HTTP/1.1\r\nA: 1\r\n".as_slice(), &[b'a'; 64]].concat()), Err((ErrorKind::HeadTooLarge, Phase::HeaderValue, 64, 431)));
+        assert_eq!(parse(&[b"GET /".as_slice(), &[b'a'; 64]].concat()), Err((ErrorKind::HeadTooLarge, Phase::Version, 64, 414)));
+    }
+
+    #[test]
+    fn test_resume() {
+        let mut buffer = BufferSlice::<4096, 4096>::from_slice(REQUEST);
+        let head = REQUEST.len() - b"body".len();
+        let mut parser = RequestParser::new();
+        for len in 0..head {
+            buffer.set_len(len as u32);
+            match parser.parse(&buffer).unwrap() {
+                Status::Partial { needed_hint } => assert!(len + needed_hint <= head),
+                status => panic!("unexpected {status:?} at {len}"),
+            }
+            assert!(parser.request(&buffer).is_none());
+        }
+        buffer.set_len(REQUEST.len() as u32);
+        assert_eq!(parser.parse(&buffer), Ok(Status::Complete(head)));
+        let request = parser.request(&buffer).unwrap();
+        assert_eq!(request.path(), b"/index.html");
+        assert_eq!(request.header(b"accept-language"), Some(b"fr".as_slice()));
+
+        parser.reset();
+        buffer.set_len(30);
+        assert_eq!(parser.parse(&buffer), Ok(Status::Partial { needed_hint: 4 }));
+        buffer.set_len(head as u32 - 1);
+        assert_eq!(parser.parse(&buffer), Ok(Status::Partial { needed_hint: 1 }));
+    }
+
+    #[test]
+    fn test_strictness() {
+        let raw = "GET /caf\u{e9} HTTP/1.1\r\nX-Name: caf\u{e9}\tb\x7f\r\n\r\n".as_bytes();
+        let buffer = BufferSlice::<4096, 4096>::from_slice(raw);
+        let parse = |strictness| {
+            let mut parser = RequestParser::new().with_strictness(strictness);
+            parser.parse(&buffer).map_err(|error| (error.kind(), error.phase(), error.offset()))
+        };
+        assert_eq!(parse(Strictness::Lenient), Ok(Status::Complete(raw.len())));
+        assert_eq!(parse(Strictness::Standard), Err((ErrorKind::InvalidToken, Phase::HeaderValue, 36)));
+        assert_eq!(parse(Strictness::Strict), Err((ErrorKind::InvalidToken, Phase::Path, 8)));
+        assert_eq!(RequestParser::new().strictness(), Strictness::Standard);
+    }
+
+    #[test]
+    fn test_decode_path() {
+        let mut buffer = BufferSlice::<8192, 4096>::from_slice(b"GET /caf%C3%A9/a%20b HTTP/1.1\r\nHost: a\r\n\r\n");
+        let mut parser = RequestParser::new();
+        assert_eq!(parser.path_range(), None);
+        assert!(matches!(parser.parse(&buffer), Ok(Status::Complete(_))));
+        let path = parser.path_range().unwrap();
+        assert_eq!(buffer.decode_in_place(path, false), Ok("/caf\u{e9}/a b"));
+        assert_eq!(parser.request(&buffer).unwrap().header(b"host"), Some(b"a".as_slice()));
+    }
+
+    #[test]
+    fn test_duplicated_headers() {
+        let parse = |raw: &[u8]| parse_request(&BufferSlice::<4096, 4096>::from_slice(raw))
+            .map(|(_, consumed)| consumed)
+            .map_err(|error| (error.kind(), error.phase(), error.offset(), error.status_code()));
+        let conflict = |offset| Err((ErrorKind::ConflictingFraming, Phase::HeaderName, offset, 400));
+        assert_eq!(parse(b"POST / HTTP/1.1\r\nContent-Length: 5\r\ncontent-length: 6\r\n\r\n"), conflict(36));
+        assert_eq!(parse(b"POST / HTTP/1.1\r\nContent-Length: 5\r\nTransfer-Encoding: chunked\r\n\r\n"), conflict(36));
+        assert_eq!(parse(b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\nContent-Length: 5\r\n\r\n"), conflict(45));
+        // same value repeated is not ambiguous
+        let raw = b"POST / HTTP/1.1\r\nContent-Length: 5\r\nContent-Length: 5\r\n\r\n";
+        assert_eq!(parse(raw), Ok(raw.len()));
+
+        let raw = b"GET / HTTP/1.1\r\nAccept: text/html\r\nHost: a\r\nACCEPT: */*\r\nTransfer-Encoding: gzip\r\nTransfer-Encoding: chunked\r\n\r\n";
+        let buffer = BufferSlice::<4096, 4096>::from_slice(raw);
+        let (request, _) = parse_request(&buffer).unwrap();
+        assert_eq!((request.headers().len(), request.headers().keys_len()), (5, 3));
+        assert_eq!(request.header(b"accept"), Some(b"text/html".as_slice()));
+        assert_eq!(request.header_all(b"accept").collect::<Vec<_>>(), [b"text/html".as_slice(), b"*/*"]);
+        assert_eq!(request.header_all(b"transfer-encoding").collect::<Vec<_>>(), [b"gzip".as_slice(), b"chunked"]);
+
+        // header count limit and map agree on repeated names
+        let limits = Limits { max_header_count: 2, ..Limits::default() };
+        let raw = b"GET / HTTP/1.1\r\nX: 1\r\nX: 2\r\nX: 3\r\n\r\n";
+        let error = parse_request_with_limits(&BufferSlice::<4096, 4096>::from_slice(raw), limits).err().unwrap();
+        assert_eq!(error.kind(), ErrorKind::TooManyHeaders);
+    }
+}
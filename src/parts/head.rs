@@ -0,0 +1,286 @@
+use crate::buffer::Buffer;
+use crate::limit::{MAX_METHOD_LENGTH, MAX_PATH_LENGTH, MAX_VERSION_LENGTH};
+use crate::offset::{HEADER_COUNT_OFFSET, HEADER_LENGTH_OFFSET, HEADER_OFFSET, HEADER_VALUE_PADDING, METHOD_OFFSET, PATH_LENGTH_OFFSET, PATH_OFFSET, VERSION_OFFSET};
+use crate::parser::{Phase, Request};
+use crate::parts::header::{AlignedHeaderKey, MAX_HEADER_KEY_LENGTH};
+
+/// Maximum amount of header keys that fit between [HEADER_OFFSET] and [PATH_OFFSET].
+pub const MAX_STORED_HEADERS: usize = (PATH_OFFSET - HEADER_OFFSET) / MAX_HEADER_KEY_LENGTH;
+const LENGTH_PREFIX: usize = size_of::<u16>();
+
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutError {
+    #[error("{0} is too long to store")]
+    TooLong(Phase),
+    #[error("too many headers to store")]
+    TooManyHeaders,
+    #[error("buffer is too small")]
+    BufferTooSmall,
+    #[error("buffer doesn't contain valid head")]
+    Corrupted,
+}
+
+#[inline(always)]
+fn read_u16(bytes: &[u8], offset: usize) -> usize {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]]) as usize
+}
+
+#[inline(always)]
+fn write_u16(bytes: &mut [u8], offset: usize, value: usize) {
+    bytes[offset..offset + LENGTH_PREFIX].copy_from_slice(&(value as u16).to_le_bytes());
+}
+
+/// Value stored in fixed 8 bytes slot, padded with 0.
+#[inline(always)]
+fn read_fixed(bytes: &[u8], offset: usize) -> &[u8] {
+    let slot = &bytes[offset..offset + 8];
+    let len = slot.iter().position(|&byte| byte == 0).unwrap_or(slot.len());
+    &slot[..len]
+}
+
+/// Request head stored inside [Buffer] with layout defined in [crate::offset].
+/// ```text
+/// HEADER_COUNT_OFFSET  u16 amount of headers
+/// HEADER_LENGTH_OFFSET u16 length of all header values including length prefix
+/// PATH_LENGTH_OFFSET   u16 length of path
+/// METHOD_OFFSET        [u8; 8] method padded with 0
+/// VERSION_OFFSET       [u8; 8] version padded with 0
+/// HEADER_OFFSET        [AlignedHeaderKey; count]
+/// PATH_OFFSET          path
+/// + path + 64 padding  (u16 length, value) * count
+/// ```
+pub struct ParsedHead<'buf> {
+    bytes: &'buf [u8],
+    count: usize,
+    path_len: usize,
+    values_len: usize,
+}
+
+impl<'buf> ParsedHead<'buf> {
+    /// Write `request` into `buffer` and return view over it, headers are stored in order of appearance.
+    pub fn store<const LEN: usize, const ALIGN: usize>(buffer: &'buf mut Buffer<LEN, ALIGN>, request: &Request<'_>) -> Result<Self, LayoutError> {
+        const { assert!(ALIGN >= MAX_HEADER_KEY_LENGTH, "header key must be aligned") };
+        let (method, path, version) = (request.method(), request.path(), request.version());
+        if method.len() > MAX_METHOD_LENGTH {
+            return Err(LayoutError::TooLong(Phase::Method));
+        }
+        if version.len() > MAX_VERSION_LENGTH {
+            return Err(LayoutError::TooLong(Phase::Version));
+        }
+        if path.len() > MAX_PATH_LENGTH {
+            return Err(LayoutError::TooLong(Phase::Path));
+        }
+        let count = request.headers().len();
+        if count > MAX_STORED_HEADERS {
+            return Err(LayoutError::TooManyHeaders);
+        }
+        let values_start = PATH_OFFSET + path.len() + HEADER_VALUE_PADDING;
+        if values_start > LEN {
+            return Err(LayoutError::BufferTooSmall);
+        }
+
+        // every limit is checked before the first write, so buffer is untouched on error
+        let mut values_len = 0;
+        for (_, value) in request.headers().iter() {
+            if value.len() > u16::MAX as usize {
+                return Err(LayoutError::TooLong(Phase::HeaderValue));
+            }
+            values_len += LENGTH_PREFIX + value.len();
+        }
+        if values_len > u16::MAX as usize {
+            return Err(LayoutError::TooLong(Phase::HeaderValue));
+        }
+        if values_start + values_len > LEN {
+            return Err(LayoutError::BufferTooSmall);
+        }
+
+        let bytes = buffer.as_mut_slice();
+        let mut cursor = values_start;
+        for (index, (key, value)) in request.headers().iter().enumerate() {
+            let value_start = cursor + LENGTH_PREFIX;
+            let key_offset = HEADER_OFFSET + index * MAX_HEADER_KEY_LENGTH;
+            bytes[key_offset..key_offset + MAX_HEADER_KEY_LENGTH].copy_from_slice(&key[..]);
+            write_u16(bytes, cursor, value.len());
+            bytes[value_start..value_start + value.len()].copy_from_slice(value);
+            cursor = value_start + value.len();
+        }
+
+        bytes[METHOD_OFFSET..VERSION_OFFSET + MAX_VERSION_LENGTH].fill(0);
+        bytes[METHOD_OFFSET..METHOD_OFFSET + method.len()].copy_from_slice(method);
+        bytes[VERSION_OFFSET..VERSION_OFFSET + version.len()].copy_from_slice(version);
+        bytes[PATH_OFFSET..PATH_OFFSET + path.len()].copy_from_slice(path);
+        write_u16(bytes, HEADER_COUNT_OFFSET, count);
+        write_u16(bytes, HEADER_LENGTH_OFFSET, values_len);
+        write_u16(bytes, PATH_LENGTH_OFFSET, path.len());
+
+        Ok(Self { bytes, count, path_len: path.len(), values_len })
+    }
+
+    /// Create view over head that previously stored by [ParsedHead::store].
+    pub fn load<const LEN: usize, const ALIGN: usize>(buffer: &'buf Buffer<LEN, ALIGN>) -> Result<Self, LayoutError> {
+        const { assert!(ALIGN >= MAX_HEADER_KEY_LENGTH, "header key must be aligned") };
+        if LEN < PATH_OFFSET {
+            return Err(LayoutError::BufferTooSmall);
+        }
+        let bytes = buffer.as_slice();
+        let count = read_u16(bytes, HEADER_COUNT_OFFSET);
+        let values_len = read_u16(bytes, HEADER_LENGTH_OFFSET);
+        let path_len = read_u16(bytes, PATH_LENGTH_OFFSET);
+        let values_start = PATH_OFFSET + path_len + HEADER_VALUE_PADDING;
+        if count > MAX_STORED_HEADERS || path_len > MAX_PATH_LENGTH || values_start + values_len > LEN {
+            return Err(LayoutError::Corrupted);
+        }
+        // every length prefix must stay inside value region, so iterator never read out of it
+        let mut cursor = values_start;
+        for _ in 0..count {
+            if cursor + LENGTH_PREFIX > values_start + values_len {
+                return Err(LayoutError::Corrupted);
+            }
+            cursor += LENGTH_PREFIX + read_u16(bytes, cursor);
+        }
+        if cursor != values_start + values_len {
+            return Err(LayoutError::Corrupted);
+        }
+
+        Ok(Self { bytes, count, path_len, values_len })
+    }
+
+    #[inline]
+    pub fn method(&self) -> &'buf [u8] {
+        read_fixed(self.bytes, METHOD_OFFSET)
+    }
+
+    #[inline]
+    pub fn version(&self) -> &'buf [u8] {
+        read_fixed(self.bytes, VERSION_OFFSET)
+    }
+
+    #[inline]
+    pub fn path(&self) -> &'buf [u8] {
+        &self.bytes[PATH_OFFSET..PATH_OFFSET + self.path_len]
+    }
+
+    #[inline]
+    pub fn header_count(&self) -> usize {
+        self.count
+    }
+
+    /// Length of all header values including length prefix.
+    #[inline]
+    pub fn header_length(&self) -> usize {
+        self.values_len
+    }
+
+    #[inline]
+    pub fn headers(&self) -> HeaderIter<'buf> {
+        HeaderIter {
+            bytes: self.bytes,
+            index: 0,
+            count: self.count,
+            cursor: PATH_OFFSET + self.path_len + HEADER_VALUE_PADDING,
+        }
+    }
+
+    /// Linear search for header with same key.
+    pub fn header(&self, key: &AlignedHeaderKey) -> Option<&'buf [u8]> {
+        self.headers()
+            .find(|(stored, _)| *stored == key)
+            .map(|(_, value)| value)
+    }
+}
+
+pub struct HeaderIter<'buf> {
+    bytes: &'buf [u8],
+    index: usize,
+    count: usize,
+    cursor: usize,
+}
+
+impl<'buf> Iterator for HeaderIter<'buf> {
+    type Item = (&'buf AlignedHeaderKey, &'buf [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index == self.count {
+            return None;
+        }
+        let key_offset = HEADER_OFFSET + self.index * MAX_HEADER_KEY_LENGTH;
+        // # Safety
+        // key slot is inside the buffer and aligned to MAX_HEADER_KEY_LENGTH since buffer alignment is checked at creation.
+        let key = unsafe { &*self.bytes[key_offset..key_offset + MAX_HEADER_KEY_LENGTH].as_ptr().cast::<AlignedHeaderKey>() };
+        let len = read_u16(self.bytes, self.cursor);
+        let start = self.cursor + LENGTH_PREFIX;
+        self.cursor = start + len;
+        self.index += 1;
+        Some((key, &self.bytes[start..start + len]))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remain = self.count - self.index;
+        (remain, Some(remain))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::BufferSlice;
+    use crate::parser::parse_request;
+
+    #[test]
+    fn test_store_load() {
+        let source = BufferSlice::<4096, 4096>::from_slice(b"GET /index.html HTTP/1.1\r\nHost: developer.mozilla.org\r\nAccept-Language: fr\r\nAccept: */*\r\n\r\n");
+        let (request, _) = parse_request(&source).unwrap();
+        let mut buffer = Buffer::<8192, 4096>::allocate();
+        let stored = ParsedHead::store(&mut buffer, &request).unwrap();
+        assert_eq!(stored.header_count(), 3);
+        assert_eq!(stored.header_length(), 3 * 2 + 21 + 2 + 3);
+
+        let head = ParsedHead::load(&buffer).unwrap();
+        assert_eq!(head.method(), b"GET");
+        assert_eq!(head.path(), b"/index.html");
+        assert_eq!(head.version(), b"HTTP/1.1");
+        assert_eq!(head.header_count(), 3);
+        assert_eq!(head.headers().count(), 3);
+        assert_eq!(head.header(&AlignedHeaderKey::new(b"Host")), Some(b"developer.mozilla.org".as_slice()));
+        assert_eq!(head.header(&AlignedHeaderKey::new(b"accept-language")), Some(b"fr".as_slice()));
+        assert_eq!(head.header(&AlignedHeaderKey::new(b"accept")), Some(b"*/*".as_slice()));
+        assert_eq!(head.header(&AlignedHeaderKey::new(b"user-agent")), None);
+    }
+
+    #[test]
+    fn test_store_order() {
+        let source = BufferSlice::<4096, 4096>::from_slice(b"GET / HTTP/1.1\r\nAccept: text/html\r\nHost: a\r\naccept: */*\r\nCookie: b\r\n\r\n");
+        let (request, _) = parse_request(&source).unwrap();
+        let mut buffer = Buffer::<8192, 4096>::allocate();
+        ParsedHead::store(&mut buffer, &request).unwrap();
+
+        let head = ParsedHead::load(&buffer).unwrap();
+        let headers = head.headers().collect::<Vec<_>>();
+        let expected = [(b"accept".as_slice(), b"text/html".as_slice()), (b"host", b"a"), (b"accept", b"*/*"), (b"cookie", b"b")];
+        assert_eq!(headers.len(), expected.len());
+        for ((key, value), (name, expected)) in headers.into_iter().zip(expected) {
+            assert!(*key == AlignedHeaderKey::new(name));
+            assert_eq!(value, expected);
+        }
+    }
+
+    #[test]
+    fn test_invalid_layout() {
+        let source = BufferSlice::<4096, 4096>::from_slice(b"GET / HTTP/1.1\r\nHost: a\r\n\r\n");
+        let (request, _) = parse_request(&source).unwrap();
+        let mut buffer = Buffer::<4096, 4096>::allocate();
+        assert_eq!(ParsedHead::store(&mut buffer, &request).err(), Some(LayoutError::BufferTooSmall));
+
+        // values don't fit, nothing is written
+        let raw = [b"GET / HTTP/1.1\r\nHost: a\r\nCookie: ".as_slice(), &[b'a'; 5000], b"\r\n\r\n"].concat();
+        let source = BufferSlice::<8192, 4096>::from_slice(&raw);
+        let (request, _) = parse_request(&source).unwrap();
+        let mut buffer = Buffer::<8192, 4096>::allocate();
+        assert_eq!(ParsedHead::store(&mut buffer, &request).err(), Some(LayoutError::BufferTooSmall));
+        assert!(buffer.as_slice().iter().all(|&byte| byte == 0));
+
+        let mut buffer = Buffer::<8192, 4096>::allocate();
+        buffer.as_mut_slice()[HEADER_COUNT_OFFSET] = 1;
+        assert_eq!(ParsedHead::load(&buffer).err(), Some(LayoutError::Corrupted));
+    }
+}
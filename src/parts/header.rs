@@ -1,5 +1,6 @@
-use crate::utils::ascii::simd_lowercase;
+use crate::utils::ascii::{is_token, simd_lowercase};
 use crate::utils::simd::aligned::Aligned32;
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::hash::{BuildHasher, Hash, Hasher};
 use std::hint::assert_unchecked;
@@ -9,10 +10,11 @@ use std::simd::num::SimdUint;
 use std::simd::Simd;
 
 // AVX2
-const MAX_HEADER_KEY_LENGTH: usize = 32;
+pub const MAX_HEADER_KEY_LENGTH: usize = 32;
 type InnerValue = Aligned32;
 
-#[derive(Eq)]
+#[derive(Eq, Clone, Copy)]
+#[repr(transparent)]
 pub struct AlignedHeaderKey(pub InnerValue);
 
 impl Deref for AlignedHeaderKey {
@@ -34,28 +36,25 @@ impl AlignedHeaderKey {
         Self(InnerValue::default())
     }
 
-
     /// # Note
     /// This function will strip value longer than [MAX_HEADER_KEY_LENGTH] bytes.
     #[inline]
     pub const fn new(value: &[u8]) -> Self {
         let mut key = AlignedHeaderKey::default();
+        let len = if value.len() < MAX_HEADER_KEY_LENGTH { value.len() } else { MAX_HEADER_KEY_LENGTH };
         unsafe {
-            std::ptr::copy_nonoverlapping(value.as_ptr(), key.0.0.as_mut_ptr(), value.len());
+            std::ptr::copy_nonoverlapping(value.as_ptr(), key.0.0.as_mut_ptr(), len);
             let lowered = simd_lowercase(Simd::from_array(key.0.0));
             std::ptr::copy_nonoverlapping(lowered.as_array().as_ptr(), key.0.0.as_mut_ptr(), lowered.len());
         }
         key
     }
-}
 
-#[derive(Eq, PartialEq, Hash)]
-struct CustomHash<'a>(&'a AlignedHeaderKey);
-
-impl<'a> CustomHash<'a> {
+    /// Build key from header name, return None if name is empty, longer than [MAX_HEADER_KEY_LENGTH]
+    /// or contains byte that is not RFC 9110 `tchar`.
     #[inline]
-    fn new(bytes: &'a AlignedHeaderKey) -> Self {
-        Self(bytes)
+    pub fn try_new(value: &[u8]) -> Option<Self> {
+        (value.len() <= MAX_HEADER_KEY_LENGTH && is_token(value)).then(|| Self::new(value))
     }
 }
 
@@ -114,25 +113,104 @@ impl BuildHasher for RandomState {
     }
 }
 
+/// Header fields of a request, every value of a repeated name is kept in order.
 pub struct HeaderMap<'a> {
-    headers: HashMap<CustomHash<'a>, &'a [u8], RandomState>,
+    /// index of first and last field of each name in `fields`
+    headers: HashMap<AlignedHeaderKey, (usize, usize), RandomState>,
+    /// name and value of every field in insertion order and index of next field with the same name
+    fields: Vec<(AlignedHeaderKey, &'a [u8], Option<usize>)>,
+}
+
+impl Default for HeaderMap<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<'a> HeaderMap<'a> {
     pub fn new() -> Self {
         Self {
             headers: HashMap::with_hasher(RandomState),
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            headers: HashMap::with_capacity_and_hasher(capacity, RandomState),
+            fields: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Insert header value, value of duplicated key is appended after the previous ones.
+    #[inline]
+    pub fn insert(&mut self, key: AlignedHeaderKey, value: &'a [u8]) {
+        let index = self.fields.len();
+        self.fields.push((key, value, None));
+        match self.headers.entry(key) {
+            Entry::Occupied(mut entry) => {
+                let (_, last) = entry.get_mut();
+                self.fields[*last].2 = Some(index);
+                *last = index;
+            }
+            Entry::Vacant(entry) => {
+                entry.insert((index, index));
+            }
+        }
+    }
+
+    /// First value of `key`.
+    #[inline]
+    pub fn get(&self, key: &AlignedHeaderKey) -> Option<&'a [u8]> {
+        self.headers.get(key).map(|&(first, _)| self.fields[first].1)
+    }
+
+    /// Every value of `key` in order of appearance.
+    #[inline]
+    pub fn get_all(&self, key: &AlignedHeaderKey) -> GetAll<'_, 'a> {
+        GetAll {
+            fields: &self.fields,
+            next: self.headers.get(key).map(|&(first, _)| first),
         }
     }
 
+    /// Amount of fields, repeated names are counted once per field.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Amount of distinct names.
     #[inline]
-    pub fn insert(&mut self, key: &'a AlignedHeaderKey, value: &'a [u8]) {
-        self.headers.insert(CustomHash::new(key), value);
+    pub fn keys_len(&self) -> usize {
+        self.headers.len()
     }
 
     #[inline]
-    pub fn get(&self, key: &AlignedHeaderKey) -> Option<&[u8]> {
-        self.headers.get(&CustomHash::new(key)).copied()
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Iterate over every field in order of appearance.
+    pub fn iter(&self) -> impl Iterator<Item=(&AlignedHeaderKey, &'a [u8])> + '_ {
+        self.fields.iter().map(|(key, value, _)| (key, *value))
+    }
+}
+
+/// Iterator over values of one name, see [HeaderMap::get_all].
+pub struct GetAll<'m, 'a> {
+    fields: &'m [(AlignedHeaderKey, &'a [u8], Option<usize>)],
+    next: Option<usize>,
+}
+
+impl<'a> Iterator for GetAll<'_, 'a> {
+    type Item = &'a [u8];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (_, value, next) = self.fields[self.next?];
+        self.next = next;
+        Some(value)
     }
 }
 
@@ -145,17 +223,44 @@ mod tests {
         let mut map = HeaderMap::new();
         unsafe {
             let key = AlignedHeaderKey::new(b"key");
-            map.insert(&key, b"value1");
+            map.insert(key, b"value1");
             assert_eq!(map.get(&AlignedHeaderKey::new(b"key")), Some(b"value1".as_slice()));
             assert_eq!(map.get(&AlignedHeaderKey::new(b"Key")), Some(b"value1".as_slice()));
             assert_eq!(map.get(&AlignedHeaderKey::new(b"KEy")), Some(b"value1".as_slice()));
             assert_eq!(map.get(&AlignedHeaderKey::new(b"KEY")), Some(b"value1".as_slice()));
 
             let key = AlignedHeaderKey::new(b"User-Agent");
-            map.insert(&key, b"value2");
+            map.insert(key, b"value2");
             assert_eq!(map.get(&AlignedHeaderKey::new(b"user-agent")), Some(b"value2".as_slice()));
             assert_eq!(map.get(&AlignedHeaderKey::new(b"User-AGent")), Some(b"value2".as_slice()));
             assert_eq!(map.get(&AlignedHeaderKey::new(b"USER-AGENT")), Some(b"value2".as_slice()));
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_try_new() {
+        assert!(AlignedHeaderKey::try_new(b"Content-Length") == Some(AlignedHeaderKey::new(b"content-length")));
+        assert!(AlignedHeaderKey::try_new(b"Content Length").is_none());
+        assert!(AlignedHeaderKey::try_new(b"").is_none());
+        assert!(AlignedHeaderKey::try_new(b"X-\x7f").is_none());
+        assert!(AlignedHeaderKey::try_new(&[b'a'; MAX_HEADER_KEY_LENGTH + 1]).is_none());
+        // longer value is stripped instead of overflow the key
+        assert!(AlignedHeaderKey::new(&[b'A'; 40]) == AlignedHeaderKey::new(&[b'a'; MAX_HEADER_KEY_LENGTH]));
+    }
+
+    #[test]
+    fn test_duplicated_key() {
+        let mut map = HeaderMap::new();
+        map.insert(AlignedHeaderKey::new(b"Accept"), b"text/html");
+        map.insert(AlignedHeaderKey::new(b"Host"), b"a");
+        map.insert(AlignedHeaderKey::new(b"accept"), b"*/*");
+        map.insert(AlignedHeaderKey::new(b"ACCEPT"), b"image/png");
+        assert_eq!((map.len(), map.keys_len()), (4, 2));
+        assert_eq!(map.get(&AlignedHeaderKey::new(b"accept")), Some(b"text/html".as_slice()));
+        let all = map.get_all(&AlignedHeaderKey::new(b"accept")).collect::<Vec<_>>();
+        assert_eq!(all, [b"text/html".as_slice(), b"*/*", b"image/png"]);
+        assert_eq!(map.get_all(&AlignedHeaderKey::new(b"missing")).count(), 0);
+        let order = map.iter().map(|(_, value)| value).collect::<Vec<_>>();
+        assert_eq!(order, [b"text/html".as_slice(), b"a", b"*/*", b"image/png"]);
+    }
+}
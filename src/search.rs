@@ -0,0 +1,651 @@
+use crate::buffer::{BufferSlice, PaddedHaystack};
+use crate::utils::dispatch;
+use crate::utils::dispatch::{EqMask, Kernel};
+use crate::utils::simd::byte_set::ByteSet;
+use crate::utils::simd::iter::{SimdFindIter, SimdRFindIter, SimdSetIter};
+use crate::utils::simd::search::Broadcast;
+use crate::utils::simd::tail_mask;
+
+const PROCESS_SIZE: usize = 64;
+
+/// Return `haystack` extended to 64 bytes boundary if it satisfy contract of [dispatch::search],
+/// the search kernels may read one more block and `needle_len` bytes after the end.
+#[inline]
+fn padded<const LEN: usize, const ALIGN: usize>(haystack: &BufferSlice<LEN, ALIGN>, needle_len: usize) -> Option<&[u8]> {
+    // # Safety
+    // pointer is only used to create slice after checked that it is inside the buffer
+    let ptr = unsafe { haystack.ptr() };
+    if !ptr.is_aligned_to(PROCESS_SIZE) {
+        return None;
+    }
+    let len = haystack.len().max(1).next_multiple_of(PROCESS_SIZE);
+    if len + PROCESS_SIZE + needle_len > haystack.capacity() {
+        return None;
+    }
+    // # Safety
+    // bytes after len are still inside the buffer and initialized at allocation
+    Some(unsafe { std::slice::from_raw_parts(ptr, len) })
+}
+
+/// Run `search` over padded `haystack` and drop match located in padding,
+/// fallback to [memchr::memmem] when buffer doesn't have enough capacity for padding.
+#[inline(always)]
+fn find_padded<const LEN: usize, const ALIGN: usize>(haystack: &BufferSlice<LEN, ALIGN>, needle: &[u8], search: impl FnOnce(&[u8]) -> usize) -> Option<usize> {
+    find_padded_or(haystack, needle, search, |haystack| memchr::memmem::find(haystack, needle))
+}
+
+/// Same as [find_padded] with custom `fallback`.
+#[inline(always)]
+fn find_padded_or<const LEN: usize, const ALIGN: usize>(
+    haystack: &BufferSlice<LEN, ALIGN>,
+    needle: &[u8],
+    search: impl FnOnce(&[u8]) -> usize,
+    fallback: impl FnOnce(&[u8]) -> Option<usize>,
+) -> Option<usize> {
+    if needle.len() > haystack.len() {
+        return None;
+    }
+    search_padded(haystack, padded(haystack, needle.len()), needle.len(), search, fallback)
+}
+
+/// Return padded slice of [PaddedHaystack] extended to 64 bytes boundary if its padding is enough for `needle_len`,
+/// which is always the case for needle of up to 64 bytes.
+#[inline]
+fn padded_in<H: PaddedHaystack + ?Sized>(haystack: &H, needle_len: usize) -> Option<&[u8]> {
+    let len = haystack.data().len().max(1).next_multiple_of(PROCESS_SIZE);
+    let padded = haystack.padded();
+    (len + PROCESS_SIZE + needle_len <= padded.len()).then(|| &padded[..len])
+}
+
+/// Run `search` over `padded` and drop match located in padding, run `fallback` over `data` without padded slice.
+#[inline(always)]
+fn search_padded(
+    data: &[u8],
+    padded: Option<&[u8]>,
+    needle_len: usize,
+    search: impl FnOnce(&[u8]) -> usize,
+    fallback: impl FnOnce(&[u8]) -> Option<usize>,
+) -> Option<usize> {
+    let Some(padded) = padded else {
+        return fallback(data);
+    };
+    let pos = search(padded);
+    // match in padding is not part of the data, any real match would be found before it
+    (pos + needle_len <= data.len()).then_some(pos)
+}
+
+/// Find first occurrence of `needle` in `haystack`, use SIMD search when buffer has enough capacity after the data,
+/// otherwise fallback to [memchr::memmem].
+#[inline]
+pub fn find<const LEN: usize, const ALIGN: usize, const NEEDLE_SIZE: usize>(haystack: &BufferSlice<LEN, ALIGN>, needle: &[u8; NEEDLE_SIZE]) -> Option<usize> {
+    // # Safety
+    // alignment and length of padded slice are checked by find_padded
+    find_padded(haystack, needle, |padded| unsafe { dispatch::search(padded, needle) })
+}
+
+/// Same as [find] over [PaddedHaystack], padding is guaranteed by the type so SIMD search is used
+/// for any needle of up to 64 bytes.
+#[inline]
+pub fn find_in<H: PaddedHaystack + ?Sized, const NEEDLE_SIZE: usize>(haystack: &H, needle: &[u8; NEEDLE_SIZE]) -> Option<usize> {
+    let data = haystack.data();
+    if NEEDLE_SIZE > data.len() {
+        return None;
+    }
+    // # Safety
+    // alignment is guaranteed by PaddedHaystack and length of padded slice is checked by padded_in
+    search_padded(data, padded_in(haystack, NEEDLE_SIZE), NEEDLE_SIZE, |padded| unsafe { dispatch::search(padded, needle) }, |data| memchr::memmem::find(data, needle))
+}
+
+/// Prebuilt searcher for needle known only at runtime, reusable across many haystacks.
+///
+/// Search function is chosen once by needle length and instruction set, and first and last byte
+/// of needle are broadcast once into vectors that the search function loads.
+#[derive(Clone, Copy)]
+pub struct Finder<'n> {
+    broadcast: Broadcast,
+    needle: &'n [u8],
+    kernel: Kernel,
+}
+
+impl<'n> Finder<'n> {
+    pub fn new(needle: &'n [u8]) -> Self {
+        Self {
+            broadcast: Broadcast::new(needle),
+            needle,
+            kernel: dispatch::kernel(dispatch::isa(), needle.len()),
+        }
+    }
+
+    #[inline]
+    pub fn needle(&self) -> &'n [u8] {
+        self.needle
+    }
+
+    /// Same as [find] but use prebuilt search function.
+    #[inline]
+    pub fn find<const LEN: usize, const ALIGN: usize>(&self, haystack: &BufferSlice<LEN, ALIGN>) -> Option<usize> {
+        // # Safety
+        // alignment and length of padded slice are checked by find_padded
+        find_padded(haystack, self.needle, |padded| unsafe { self.find_unchecked(padded) })
+    }
+
+    /// Same as [find_in] but use prebuilt search function.
+    #[inline]
+    pub fn find_in<H: PaddedHaystack + ?Sized>(&self, haystack: &H) -> Option<usize> {
+        let data = haystack.data();
+        if self.needle.len() > data.len() {
+            return None;
+        }
+        // # Safety
+        // alignment is guaranteed by PaddedHaystack and length of padded slice is checked by padded_in
+        search_padded(data, padded_in(haystack, self.needle.len()), self.needle.len(), |padded| unsafe { self.find_unchecked(padded) }, |data| memchr::memmem::find(data, self.needle))
+    }
+
+    /// Return `haystack.len()` if needle not found.
+    /// # Safety
+    /// Same as [dispatch::search], haystack must be 64 bytes aligned, length must be divisible by 64
+    /// and >= max(64, needle.len()), and memory must be readable up to length + 64 + needle.len().
+    #[inline]
+    pub unsafe fn find_unchecked(&self, haystack: &[u8]) -> usize {
+        (self.kernel)(haystack, self.needle, &self.broadcast)
+    }
+}
+
+/// Scalar case-insensitive search used when buffer doesn't have enough capacity for padding.
+#[inline]
+fn find_ignore_case_scalar(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(0);
+    }
+    haystack.windows(needle.len()).position(|window| window.eq_ignore_ascii_case(needle))
+}
+
+/// Same as [find] but match ASCII letters ignoring case, e.g. `chunked` in `Transfer-Encoding: Chunked`.
+#[inline]
+pub fn find_ignore_ascii_case<const LEN: usize, const ALIGN: usize, const NEEDLE_SIZE: usize>(haystack: &BufferSlice<LEN, ALIGN>, needle: &[u8; NEEDLE_SIZE]) -> Option<usize> {
+    // # Safety
+    // alignment and length of padded slice are checked by find_padded_or
+    find_padded_or(haystack, needle, |padded| unsafe { dispatch::search_ignore_ascii_case(padded, needle) }, |haystack| find_ignore_case_scalar(haystack, needle))
+}
+
+/// Case-folding counterpart of [Finder], needle is lowercased once and each block of haystack is lowercased on the fly.
+#[derive(Clone)]
+pub struct FinderIgnoreAsciiCase {
+    broadcast: Broadcast,
+    needle: Box<[u8]>,
+    kernel: Kernel,
+}
+
+impl FinderIgnoreAsciiCase {
+    pub fn new(needle: &[u8]) -> Self {
+        let needle = needle.to_ascii_lowercase().into_boxed_slice();
+        Self {
+            broadcast: Broadcast::new(&needle),
+            kernel: dispatch::kernel_ignore_case(dispatch::isa(), needle.len()),
+            needle,
+        }
+    }
+
+    /// Lowercased needle.
+    #[inline]
+    pub fn needle(&self) -> &[u8] {
+        &self.needle
+    }
+
+    /// Same as [find_ignore_ascii_case] but use prebuilt search function.
+    #[inline]
+    pub fn find<const LEN: usize, const ALIGN: usize>(&self, haystack: &BufferSlice<LEN, ALIGN>) -> Option<usize> {
+        // # Safety
+        // alignment and length of padded slice are checked by find_padded_or
+        find_padded_or(haystack, &self.needle, |padded| unsafe { self.find_unchecked(padded) }, |haystack| find_ignore_case_scalar(haystack, &self.needle))
+    }
+
+    /// Return `haystack.len()` if needle not found.
+    /// # Safety
+    /// Same as [dispatch::search], haystack must be 64 bytes aligned, length must be divisible by 64
+    /// and >= max(64, needle.len()), and memory must be readable up to length + 64 + needle.len().
+    #[inline]
+    pub unsafe fn find_unchecked(&self, haystack: &[u8]) -> usize {
+        (self.kernel)(haystack, &self.needle, &self.broadcast)
+    }
+}
+
+/// Iterate over start position of every occurrence of `needle` in `haystack`, occurrences may overlap
+/// unless [FindIter::non_overlapping] is used.
+#[inline]
+pub fn find_iter<'a, const LEN: usize, const ALIGN: usize>(haystack: &'a BufferSlice<LEN, ALIGN>, needle: &'a [u8]) -> FindIter<'a> {
+    let inner = match blocks(haystack) {
+        // # Safety
+        // slice is aligned and readable up to length rounded up to 64 bytes
+        Some(blocks) if !needle.is_empty() => Inner::Simd(unsafe { SimdFindIter::new(blocks, needle) }),
+        _ => Inner::Memmem { haystack, position: 0 },
+    };
+    FindIter { inner, needle, step: 1 }
+}
+
+// iterator is kept on the stack, boxing the broadcast vectors would allocate for every search
+#[allow(clippy::large_enum_variant)]
+enum Inner<'a> {
+    Simd(SimdFindIter<'a>),
+    Memmem { haystack: &'a [u8], position: usize },
+}
+
+/// Iterator returned from [find_iter].
+pub struct FindIter<'a> {
+    inner: Inner<'a>,
+    needle: &'a [u8],
+    /// Distance from start of occurrence to start of next search for memmem fallback.
+    step: usize,
+}
+
+impl<'a> FindIter<'a> {
+    /// Continue search after the end of each occurrence, e.g. iterate `--boundary` or `\r\n` pairs.
+    #[inline]
+    pub fn non_overlapping(mut self) -> Self {
+        self.step = self.needle.len().max(1);
+        if let Inner::Simd(iter) = self.inner {
+            self.inner = Inner::Simd(iter.non_overlapping());
+        }
+        self
+    }
+}
+
+impl<'a> Iterator for FindIter<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            Inner::Simd(iter) => iter.next(),
+            Inner::Memmem { haystack, position } => {
+                let pos = *position + memchr::memmem::find(haystack.get(*position..)?, self.needle)?;
+                *position = pos + self.step;
+                Some(pos)
+            }
+        }
+    }
+}
+
+/// Return `haystack` if it satisfy contract of [dispatch::rfind], reverse search never read after
+/// the last block so only capacity up to length rounded up to 64 bytes is needed.
+#[inline]
+fn blocks<const LEN: usize, const ALIGN: usize>(haystack: &BufferSlice<LEN, ALIGN>) -> Option<&[u8]> {
+    // # Safety
+    // pointer is only used to check alignment
+    let aligned = unsafe { haystack.ptr() }.is_aligned_to(PROCESS_SIZE);
+    (aligned && haystack.len().next_multiple_of(PROCESS_SIZE) <= haystack.capacity()).then_some(haystack)
+}
+
+/// Find last occurrence of `needle` in `haystack`, fallback to [memchr::memmem] when buffer is not aligned.
+#[inline]
+pub fn rfind<const LEN: usize, const ALIGN: usize, const NEEDLE_SIZE: usize>(haystack: &BufferSlice<LEN, ALIGN>, needle: &[u8; NEEDLE_SIZE]) -> Option<usize> {
+    match blocks(haystack) {
+        // # Safety
+        // alignment and capacity are checked by blocks
+        Some(blocks) => unsafe { dispatch::rfind(blocks, needle) },
+        None => memchr::memmem::rfind(haystack, needle),
+    }
+}
+
+/// Same as [rfind] over [PaddedHaystack], never fallback.
+#[inline]
+pub fn rfind_in<H: PaddedHaystack + ?Sized, const NEEDLE_SIZE: usize>(haystack: &H, needle: &[u8; NEEDLE_SIZE]) -> Option<usize> {
+    // # Safety
+    // PaddedHaystack is aligned and readable past the data rounded up to 64 bytes
+    unsafe { dispatch::rfind(haystack.data(), needle) }
+}
+
+/// Iterate over start position of every occurrence of `needle` in `haystack` from the end, occurrences may overlap.
+#[inline]
+pub fn rfind_iter<'a, const LEN: usize, const ALIGN: usize>(haystack: &'a BufferSlice<LEN, ALIGN>, needle: &'a [u8]) -> RFindIter<'a> {
+    let inner = match blocks(haystack) {
+        Some(blocks) if !needle.is_empty() => {
+            // # Safety
+            // slice is aligned and readable up to length rounded up to 64 bytes
+            RInner::Simd(unsafe { SimdRFindIter::new(blocks, needle) })
+        }
+        _ => RInner::Memmem { haystack, end: haystack.len() },
+    };
+    RFindIter { inner, needle }
+}
+
+enum RInner<'a> {
+    Simd(SimdRFindIter<'a>),
+    Memmem { haystack: &'a [u8], end: usize },
+    Done,
+}
+
+/// Iterator returned from [rfind_iter].
+pub struct RFindIter<'a> {
+    inner: RInner<'a>,
+    needle: &'a [u8],
+}
+
+impl<'a> Iterator for RFindIter<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            RInner::Simd(iter) => iter.next(),
+            RInner::Memmem { haystack, end } => {
+                let pos = memchr::memmem::rfind(&haystack[..*end], self.needle);
+                // next occurrence may overlap, so it must end before the last byte of this one
+                match pos.and_then(|pos| (pos + self.needle.len()).checked_sub(1)) {
+                    Some(next_end) => *end = next_end,
+                    None => self.inner = RInner::Done,
+                }
+                pos
+            }
+            RInner::Done => None,
+        }
+    }
+}
+
+/// Find first byte of `haystack` that is in `set`, fallback to scalar lookup when buffer is not aligned.
+#[inline]
+pub fn find_set<const LEN: usize, const ALIGN: usize>(haystack: &BufferSlice<LEN, ALIGN>, set: &ByteSet) -> Option<usize> {
+    match blocks(haystack) {
+        // # Safety
+        // alignment and capacity are checked by blocks
+        Some(blocks) => unsafe { dispatch::index_of_set(blocks, set) },
+        None => haystack.iter().position(|&byte| set.contains(byte)),
+    }
+}
+
+/// Same as [find_set] over [PaddedHaystack], never fallback.
+#[inline]
+pub fn find_set_in<H: PaddedHaystack + ?Sized>(haystack: &H, set: &ByteSet) -> Option<usize> {
+    // # Safety
+    // PaddedHaystack is aligned and readable past the data rounded up to 64 bytes
+    unsafe { dispatch::index_of_set(haystack.data(), set) }
+}
+
+/// Iterate over position of every byte of `haystack` that is in `set`.
+#[inline]
+pub fn find_set_iter<'a, const LEN: usize, const ALIGN: usize>(haystack: &'a BufferSlice<LEN, ALIGN>, set: &ByteSet) -> SetIter<'a> {
+    let inner = match blocks(haystack) {
+        // # Safety
+        // slice is aligned and readable up to length rounded up to 64 bytes
+        Some(blocks) => SetInner::Simd(unsafe { SimdSetIter::new(blocks, set) }),
+        None => SetInner::Scalar { haystack, set: *set, position: 0 },
+    };
+    SetIter { inner }
+}
+
+enum SetInner<'a> {
+    Simd(SimdSetIter<'a>),
+    Scalar { haystack: &'a [u8], set: ByteSet, position: usize },
+}
+
+/// Iterator returned from [find_set_iter].
+pub struct SetIter<'a> {
+    inner: SetInner<'a>,
+}
+
+impl<'a> Iterator for SetIter<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            SetInner::Simd(iter) => iter.next(),
+            SetInner::Scalar { haystack, set, position } => {
+                let pos = *position + haystack[*position..].iter().position(|&byte| set.contains(byte))?;
+                *position = pos + 1;
+                Some(pos)
+            }
+        }
+    }
+}
+
+/// Iterate over bitmask of bytes equal to `needle` for each 64 bytes block of `haystack`,
+/// bits after the end of haystack are cleared.
+#[inline]
+pub fn match_blocks<const LEN: usize, const ALIGN: usize>(haystack: &BufferSlice<LEN, ALIGN>, needle: u8) -> BlockMasks<'_> {
+    BlockMasks {
+        haystack,
+        needle,
+        offset: 0,
+        readable: haystack.len().next_multiple_of(PROCESS_SIZE) <= haystack.capacity(),
+        mask: dispatch::eq_mask(dispatch::isa()),
+    }
+}
+
+/// Bitmask of bytes equal to `needle` for every 64 bytes block of `haystack`,
+/// structural index of the head can be built from it in a single pass.
+#[inline]
+pub fn match_bitmap<const LEN: usize, const ALIGN: usize>(haystack: &BufferSlice<LEN, ALIGN>, needle: u8) -> Vec<u64> {
+    match_blocks(haystack, needle).collect()
+}
+
+/// Iterator returned from [match_blocks].
+pub struct BlockMasks<'a> {
+    haystack: &'a [u8],
+    needle: u8,
+    offset: usize,
+    /// Last partial block can be loaded whole, otherwise it is compared byte by byte.
+    readable: bool,
+    mask: EqMask,
+}
+
+impl<'a> Iterator for BlockMasks<'a> {
+    type Item = u64;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let remain = self.haystack.len().checked_sub(self.offset).filter(|&remain| remain > 0)?;
+        let mask = if remain >= PROCESS_SIZE || self.readable {
+            // # Safety
+            // block is inside the haystack or inside buffer capacity
+            unsafe { (self.mask)(self.haystack.as_ptr().add(self.offset), self.needle) & tail_mask(remain) }
+        } else {
+            self.haystack[self.offset..].iter().rev().fold(0, |mask, &byte| mask << 1 | (byte == self.needle) as u64)
+        };
+        self.offset += PROCESS_SIZE;
+        Some(mask)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let blocks = self.haystack.len().saturating_sub(self.offset).div_ceil(PROCESS_SIZE);
+        (blocks, Some(blocks))
+    }
+}
+
+impl ExactSizeIterator for BlockMasks<'_> {}
+
+/// Count non-overlapping occurrences of `needle` in `haystack`, same as [str::matches] count,
+/// e.g. CRLF count of the head give upper bound of header lines to pre-size [crate::parts::header::HeaderMap].
+pub fn count<const LEN: usize, const ALIGN: usize>(haystack: &BufferSlice<LEN, ALIGN>, needle: &[u8]) -> usize {
+    match needle {
+        [] => haystack.len() + 1,
+        [byte] => match_blocks(haystack, *byte).map(|mask| mask.count_ones() as usize).sum(),
+        _ => find_iter(haystack, needle).non_overlapping().count(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::growable::GrowableBuffer;
+    use crate::buffer::size_class::ClassBuffer;
+    use crate::buffer::Buffer;
+
+    static DATA: &[u8] = b"GET /index.html HTTP/1.1\r\nHost: developer.mozilla.org\r\nAccept-Language: fr\r\nUser-Agent: Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/108.0.0.0 Safari/537.36\r\n\r\n";
+
+    fn check<const N: usize, const LEN: usize, const ALIGN: usize>(haystack: &BufferSlice<LEN, ALIGN>, needle: &[u8; N]) {
+        assert_eq!(memchr::memmem::find(haystack, needle), find(haystack, needle), "{:?}", std::str::from_utf8(needle));
+        let expected = (0..=haystack.len().saturating_sub(N))
+            .filter(|&pos| haystack[pos..].starts_with(needle))
+            .collect::<Vec<_>>();
+        assert_eq!(expected, find_iter(haystack, needle).collect::<Vec<_>>(), "{:?}", std::str::from_utf8(needle));
+        assert_eq!(memchr::memmem::rfind(haystack, needle), rfind(haystack, needle), "{:?}", std::str::from_utf8(needle));
+        let reversed = expected.into_iter().rev().collect::<Vec<_>>();
+        assert_eq!(reversed, rfind_iter(haystack, needle).collect::<Vec<_>>(), "{:?}", std::str::from_utf8(needle));
+    }
+
+    #[test]
+    fn test_find() {
+        let haystack = BufferSlice::<4096, 4096>::from_slice(DATA);
+        check(&haystack, b"G");
+        check(&haystack, b"\r\n");
+        check(&haystack, b"\r\n\r\n");
+        check(&haystack, b"Mozilla");
+        check(&haystack, b"Safari/537.36\r\n\r\n");
+        check(&haystack, b"missing");
+        check(&haystack, b"Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/108.0.0.0");
+        check(&haystack, b"");
+
+        // data after len must not be reported
+        let mut haystack = BufferSlice::<4096, 4096>::from_slice(DATA);
+        haystack.set_len(20);
+        check(&haystack, b"HTTP/1.1");
+        check(&haystack, b"Host");
+        check(&haystack, b"\r\n");
+
+        // not enough capacity for padding, fallback to memmem
+        let haystack = BufferSlice::<128, 4096>::from_slice(&DATA[..100]);
+        check(&haystack, b"Host");
+        check(&haystack, b"fr");
+
+        // unaligned start, fallback to memmem
+        let mut haystack = Buffer::<4096, 4096>::allocate().slice(3);
+        haystack.set_len(10);
+        assert_eq!(find(&haystack, b"\0\0"), Some(0));
+    }
+
+    #[test]
+    fn test_finder() {
+        let haystack = BufferSlice::<4096, 4096>::from_slice(DATA);
+        for needle_len in 0..=DATA.len() {
+            for from in [0, DATA.len() - needle_len] {
+                let finder = Finder::new(&DATA[from..from + needle_len]);
+                assert_eq!(memchr::memmem::find(DATA, finder.needle()), finder.find(&haystack), "{from} {needle_len}");
+            }
+        }
+        let finder = Finder::new(b"\r\n");
+        let short = BufferSlice::<4096, 4096>::from_slice(b"GET / HTTP/1.1\r\n");
+        assert_eq!(finder.find(&haystack), Some(24));
+        assert_eq!(finder.find(&short), Some(14));
+        assert_eq!(Finder::new(b"missing").find(&haystack), None);
+    }
+
+    #[test]
+    fn test_find_iter_overlapped() {
+        let haystack = BufferSlice::<4096, 4096>::from_slice(b"aaaa abab ababab");
+        assert_eq!(find_iter(&haystack, b"aa").collect::<Vec<_>>(), [0, 1, 2]);
+        assert_eq!(find_iter(&haystack, b"abab").collect::<Vec<_>>(), [5, 10, 12]);
+        let haystack = BufferSlice::<128, 4096>::from_slice(b"aaaa abab ababab");
+        assert_eq!(find_iter(&haystack, b"abab").collect::<Vec<_>>(), [5, 10, 12]);
+        assert_eq!(rfind_iter(&haystack, b"abab").collect::<Vec<_>>(), [12, 10, 5]);
+        assert_eq!(find_iter(&haystack, b"abab").non_overlapping().collect::<Vec<_>>(), [5, 10]);
+        let haystack = BufferSlice::<4096, 4096>::from_slice(b"aaaa abab ababab");
+        assert_eq!(rfind_iter(&haystack, b"aa").collect::<Vec<_>>(), [2, 1, 0]);
+        assert_eq!(find_iter(&haystack, b"aa").non_overlapping().collect::<Vec<_>>(), [0, 2]);
+        // last space of request line
+        let haystack = BufferSlice::<4096, 4096>::from_slice(b"GET /a b HTTP/1.1");
+        assert_eq!(rfind(&haystack, b" "), Some(8));
+    }
+
+    #[test]
+    fn test_find_set() {
+        let set = ByteSet::new(b"\r\n:");
+        let haystack = BufferSlice::<4096, 4096>::from_slice(DATA);
+        let expected = (0..DATA.len()).filter(|&i| set.contains(DATA[i])).collect::<Vec<_>>();
+        assert_eq!(expected.first().copied(), find_set(&haystack, &set));
+        assert_eq!(expected, find_set_iter(&haystack, &set).collect::<Vec<_>>());
+
+        // unaligned start, fallback to scalar
+        let buffer = Buffer::<4096, 4096>::allocate();
+        let mut unaligned = buffer.slice(3);
+        unaligned.set_len(10);
+        assert_eq!(find_set(&unaligned, &ByteSet::new(b"\0")), Some(0));
+        assert_eq!(find_set_iter(&unaligned, &set).next(), None);
+    }
+
+    #[test]
+    fn test_count() {
+        let haystack = BufferSlice::<4096, 4096>::from_slice(DATA);
+        assert_eq!(count(&haystack, b"\r\n"), 5);
+        assert_eq!(count(&haystack, b"\n"), 5);
+        assert_eq!(count(&haystack, b"Mozilla"), 1);
+        assert_eq!(count(&haystack, b""), DATA.len() + 1);
+        let haystack = BufferSlice::<4096, 4096>::from_slice(b"aaaaa");
+        assert_eq!(count(&haystack, b"aa"), 2);
+        assert_eq!(count(&haystack, b"a"), 5);
+    }
+
+    #[test]
+    fn test_match_bitmap() {
+        let bitmap = |bytes: &[u8]| {
+            bytes
+                .chunks(64)
+                .map(|chunk| chunk.iter().enumerate().filter(|(_, &byte)| byte == b'\n').fold(0u64, |mask, (i, _)| mask | 1 << i))
+                .collect::<Vec<_>>()
+        };
+        for len in [0, 1, 63, 64, 65, 100, DATA.len()] {
+            let mut haystack = BufferSlice::<4096, 4096>::from_slice(DATA);
+            haystack.set_len(len as u32);
+            assert_eq!(bitmap(&DATA[..len]), match_bitmap(&haystack, b'\n'), "{len}");
+            // last block can't be loaded whole
+            let len = len.min(100);
+            let mut buffer = Buffer::<128, 4096>::allocate();
+            buffer.as_mut_slice()[3..3 + len].copy_from_slice(&DATA[..len]);
+            let mut haystack = buffer.slice(3);
+            haystack.set_len(len as u32);
+            assert_eq!(bitmap(&DATA[..len]), match_bitmap(&haystack, b'\n'), "{len}");
+        }
+    }
+
+    #[test]
+    fn test_find_ignore_ascii_case() {
+        let haystack = BufferSlice::<4096, 4096>::from_slice(DATA);
+        let lower = DATA.to_ascii_lowercase();
+        for needle in [b"host".as_slice(), b"MOZILLA", b"user-agent: mozilla/5.0 (x11; linux x86_64) applewebkit/537.36 (khtml, like gecko)", b"missing", b""] {
+            let expected = memchr::memmem::find(&lower, &needle.to_ascii_lowercase());
+            assert_eq!(expected, FinderIgnoreAsciiCase::new(needle).find(&haystack), "{:?}", std::str::from_utf8(needle));
+            // not enough capacity for padding, fallback to scalar
+            let short = BufferSlice::<256, 4096>::from_slice(DATA);
+            assert_eq!(expected, FinderIgnoreAsciiCase::new(needle).find(&short), "{:?}", std::str::from_utf8(needle));
+        }
+        assert_eq!(find_ignore_ascii_case(&haystack, b"Accept-LANGUAGE"), Some(55));
+        let value = BufferSlice::<4096, 4096>::from_slice(b"Transfer-Encoding: gzip, Chunked");
+        assert_eq!(find_ignore_ascii_case(&value, b"chunked"), Some(25));
+        assert_eq!(FinderIgnoreAsciiCase::new(b"CHUNKED").needle(), b"chunked");
+    }
+
+    #[test]
+    fn test_find_in() {
+        fn check_in<H: PaddedHaystack, const N: usize>(haystack: &H, needle: &[u8; N]) {
+            let data = haystack.data();
+            assert_eq!(memchr::memmem::find(data, needle), find_in(haystack, needle), "{:?}", std::str::from_utf8(needle));
+            assert_eq!(memchr::memmem::find(data, needle), Finder::new(needle).find_in(haystack), "{:?}", std::str::from_utf8(needle));
+            assert_eq!(memchr::memmem::rfind(data, needle), rfind_in(haystack, needle), "{:?}", std::str::from_utf8(needle));
+        }
+
+        let mut haystack = GrowableBuffer::new4k();
+        for _ in 0..10 {
+            haystack.extend_from_slice(DATA);
+        }
+        let class = {
+            let mut buffer = ClassBuffer::allocate(0);
+            buffer.set_len(DATA.len());
+            buffer.copy_from_slice(DATA);
+            buffer
+        };
+        for needle in [b"Host".as_slice(), b"\r\n\r\n", b"missing"] {
+            let needle: &[u8; 4] = needle[..4].try_into().unwrap();
+            check_in(&haystack, needle);
+            check_in(&class, needle);
+        }
+        // longer than padding guarantee, fallback to memmem
+        check_in(&haystack, b"Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/108.0.0.0");
+        assert_eq!(find_set_in(&haystack, &ByteSet::new(b"()")), DATA.iter().position(|&byte| byte == b'('));
+
+        // data after len must not be reported
+        haystack.truncate(DATA.len() + 20);
+        check_in(&haystack, b"\r\n\r\n");
+        check_in(&haystack, b"Host");
+        haystack.truncate(3);
+        check_in(&haystack, b"GET");
+        assert_eq!(find_set_in(&haystack, &ByteSet::new(b" ")), None);
+    }
+}
@@ -1,16 +1,18 @@
-use crate::buffer::ALIGN;
 use std::hint::assert_unchecked;
 
 /// Allocates memory with the global allocator.
-/// This function forwards calls to the [std::alloc::alloc]
+/// This function forwards calls to the [std::alloc::alloc] and calls [std::alloc::handle_alloc_error] on failure.
 /// # Safety
 /// Caller must ensure size and align are power of two.
 #[inline(always)]
 pub unsafe fn alloc_u8_aligned(size: usize, align: usize) -> *mut u8 {
     let layout = std::alloc::Layout::from_size_align_unchecked(size, align);
     let ptr = std::alloc::alloc(layout);
+    if ptr.is_null() {
+        std::alloc::handle_alloc_error(layout);
+    }
 
-    assert_unchecked(ptr.is_aligned_to(ALIGN));
+    assert_unchecked(ptr.is_aligned_to(align));
     ptr
 }
 
@@ -22,4 +24,20 @@ pub unsafe fn alloc_u8_aligned(size: usize, align: usize) -> *mut u8 {
 pub unsafe fn dealloc_u8_aligned(ptr: *mut u8, size: usize, align: usize) {
     let layout = std::alloc::Layout::from_size_align_unchecked(size, align);
     std::alloc::dealloc(ptr, layout)
-}
\ No newline at end of file
+}
+
+/// Reallocates memory with the global allocator, alignment is kept.
+/// This function forwards calls to the [std::alloc::realloc] and calls [std::alloc::handle_alloc_error] on failure.
+/// # Safety
+/// Caller must ensure size and align are the same when allocated and new size is not zero.
+#[inline(always)]
+pub unsafe fn realloc_u8_aligned(ptr: *mut u8, size: usize, align: usize, new_size: usize) -> *mut u8 {
+    let layout = std::alloc::Layout::from_size_align_unchecked(size, align);
+    let ptr = std::alloc::realloc(ptr, layout, new_size);
+    if ptr.is_null() {
+        std::alloc::handle_alloc_error(std::alloc::Layout::from_size_align_unchecked(new_size, align));
+    }
+
+    assert_unchecked(ptr.is_aligned_to(align));
+    ptr
+}
@@ -1,6 +1,8 @@
 use std::intrinsics::const_eval_select;
-use std::simd::{LaneCount, Simd, SupportedLaneCount};
-use std::simd::cmp::SimdPartialOrd;
+use std::simd::{LaneCount, Mask, Simd, SupportedLaneCount};
+use std::simd::cmp::{SimdPartialEq, SimdPartialOrd};
+
+use crate::utils::simd::tail_mask;
 
 #[inline]
 fn simd_lowercase_runtime<const LANES: usize>(bytes: Simd<u8, LANES>) -> Simd<u8, LANES>
@@ -32,4 +34,73 @@ where
     LaneCount<LANES>: SupportedLaneCount,
 {
     const_eval_select((bytes,), simd_lowercase_comptime, simd_lowercase_runtime)
-}
\ No newline at end of file
+}
+
+/// Return mask of lanes that are RFC 9110 `tchar`, which is ALPHA, DIGIT or one of ``!#$%&'*+-.^_`|~``.
+#[inline]
+pub fn simd_is_tchar<const LANES: usize>(bytes: Simd<u8, LANES>) -> Mask<i8, LANES>
+where
+    LaneCount<LANES>: SupportedLaneCount,
+{
+    let between = |low: u8, high: u8| bytes.simd_ge(Simd::splat(low)) & bytes.simd_le(Simd::splat(high));
+    // setting 0x20 bit fold upper case letters into lower case, no other byte is folded into a-z
+    let folded = bytes | Simd::splat(0b100000);
+    let alpha = folded.simd_ge(Simd::splat(b'a')) & folded.simd_le(Simd::splat(b'z'));
+    alpha
+        | between(b'0', b'9')
+        | bytes.simd_eq(Simd::splat(b'!'))
+        | between(b'#', b'\'')
+        | between(b'*', b'+')
+        | between(b'-', b'.')
+        | between(b'^', b'`')
+        | bytes.simd_eq(Simd::splat(b'|'))
+        | bytes.simd_eq(Simd::splat(b'~'))
+}
+
+const TOKEN_BLOCK_SIZE: usize = 32;
+
+/// Return index of first byte that is not `tchar`, validate 32 bytes block at a time
+/// since header name is at most [crate::limit::MAX_HEADER_LENGTH] bytes.
+#[inline]
+pub fn first_invalid_tchar(bytes: &[u8]) -> Option<usize> {
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let block = Simd::<u8, TOKEN_BLOCK_SIZE>::load_or_default(&bytes[pos..]);
+        let invalid = !simd_is_tchar(block).to_bitmask() & tail_mask((bytes.len() - pos).min(TOKEN_BLOCK_SIZE));
+        if invalid != 0 {
+            return Some(pos + invalid.trailing_zeros() as usize);
+        }
+        pos += TOKEN_BLOCK_SIZE;
+    }
+    None
+}
+
+/// Return true if `bytes` is a non-empty RFC 9110 `token`.
+#[inline]
+pub fn is_token(bytes: &[u8]) -> bool {
+    !bytes.is_empty() && first_invalid_tchar(bytes).is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tchar() {
+        let expected = |byte: u8| byte.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&byte);
+        let bytes = (0..=u8::MAX).collect::<Vec<_>>();
+        for chunk in bytes.chunks(32) {
+            let mask = simd_is_tchar(Simd::<u8, 32>::from_slice(chunk));
+            for (i, &byte) in chunk.iter().enumerate() {
+                assert_eq!(expected(byte), mask.test(i), "{byte:#x}");
+            }
+        }
+        assert_eq!(first_invalid_tchar(b"Content-Type"), None);
+        assert_eq!(first_invalid_tchar(b"Host "), Some(4));
+        assert_eq!(first_invalid_tchar(b"X-\x80"), Some(2));
+        assert_eq!(first_invalid_tchar(&[b"a".repeat(40).as_slice(), b"\x01"].concat()), Some(40));
+        assert!(is_token(b"X-Forwarded-For"));
+        assert!(!is_token(b""));
+    }
+
+}
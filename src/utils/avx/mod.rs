@@ -28,13 +28,7 @@ pub const fn mask_false_i8x8(count: u32) -> u64 {
     POW256[count as usize]
 }
 
-macro_rules! avx_mask_true {
-    ($count:expr) => {
-        ((1 << $count) - 1)
-    }
-}
-
-pub(crate) use avx_mask_true;
+pub(crate) use crate::utils::simd::avx_mask_true;
 
 
 /// Generate a reverse sequence of u8 from 63 to 0
@@ -1,16 +1,30 @@
+use crate::utils::dispatch::Kernel;
 use crate::utils::avx::avx_mask_true;
+use crate::utils::simd::byte_set::{ByteSet, NIBBLE_BIT};
+use crate::utils::ascii::simd_lowercase;
+use crate::utils::simd::search::{equal_long, equal_long_ignore_case, Broadcast};
+use crate::utils::simd::tail_mask;
 use std::arch::asm;
-use std::arch::x86_64::{__mmask64, _kshiftli_mask64, _mm512_cmpeq_epi8_mask, _mm512_load_epi64, _mm512_mask_cmpeq_epi8_mask, _mm512_set1_epi8};
+use std::arch::x86_64::{__m512i, __mmask64, _kshiftli_mask64, _mm512_and_si512, _mm512_broadcast_i32x4, _mm512_cmpeq_epi8_mask, _mm512_cmplt_epu8_mask, _mm512_load_epi64, _mm512_loadu_epi8, _mm512_mask_add_epi8, _mm512_mask_cmpeq_epi8_mask, _mm512_or_si512, _mm512_set1_epi8, _mm512_shuffle_epi8, _mm512_srli_epi16, _mm512_sub_epi8, _mm512_test_epi8_mask, _mm512_xor_si512, _mm_loadu_si128};
 use std::hint::assert_unchecked;
 use std::simd::Mask;
 
+/// Load 64 bytes aligned vector prebuilt by [Broadcast].
+#[inline]
+#[target_feature(enable = "avx512f,avx512bw")]
+unsafe fn load_broadcast(ptr: *const u8) -> __m512i {
+    _mm512_load_epi64(ptr.cast())
+}
+
 macro_rules! avx_search {
     ($name:ident,$lanes:literal) => {
         /// # Safety
-        /// Haystack must be 64 bytes aligned and length must be >= 64 + needle.len() and divisible by 64.
+        /// Haystack must be 64 bytes aligned, length must be divisible by 64 and >= max(64, needle.len()),
+        /// and memory must be readable up to length + 64 + needle.len().
         /// you can use any u8 that not included in needle as padding.
         #[inline(never)]
-        unsafe fn $name(haystack: &[u8], needle: &[u8]) -> usize {
+        #[target_feature(enable = "avx512f,avx512bw")]
+        unsafe fn $name(haystack: &[u8], needle: &[u8], broadcast: &Broadcast) -> usize {
             let len = haystack.len();
             let end = haystack.as_ptr().add(len - needle.len());
             assert_unchecked(len >= 64);
@@ -24,8 +38,7 @@ macro_rules! avx_search {
             // let mut needle64 = [0; 8];
             // copy_nonoverlapping(needle.as_ptr(), needle64.as_mut_ptr(), needle.len());
             // let needle64 = u64::from_le_bytes(needle64);
-            let needle_head = needle[0];
-            let head = _mm512_set1_epi8(needle_head as i8);
+            let head = load_broadcast(broadcast.head_ptr());
             loop {
                 // mark last element as 0 to avoid out of bounds
                 // let vector = (!mask_false(mask.to_bitmask().leading_zeros() as usize - 1)).select(vector, Simd::splat(0));
@@ -58,17 +71,18 @@ macro_rules! avx_search {
 macro_rules! avx_search_sub_1 {
     ($name:ident,$lanes:literal) => {
         /// # Safety
-        /// Haystack must be 64 bytes aligned and length must be >= 64 + needle.len() and divisible by 64.
+        /// Haystack must be 64 bytes aligned, length must be divisible by 64 and >= max(64, needle.len()),
+        /// and memory must be readable up to length + 64 + needle.len().
         /// you can use any u8 that not included in needle as padding.
         #[inline(never)]
-        unsafe fn $name(haystack: &[u8], needle: &[u8]) -> usize {
+        #[target_feature(enable = "avx512f,avx512bw")]
+        unsafe fn $name(haystack: &[u8], needle: &[u8], broadcast: &Broadcast) -> usize {
             let len = haystack.len();
             let end = haystack.as_ptr().add(len - needle.len());
             assert_unchecked(len >= 64);
             assert_unchecked(needle.len() == $lanes + 1);
 
-            let needle_head = needle[0];
-            let head = _mm512_set1_epi8(needle_head as i8);
+            let head = load_broadcast(broadcast.head_ptr());
             let mut ptr = haystack.as_ptr() as *const i64;
             assert_unchecked(ptr.is_aligned_to(64));
             let needle = std::simd::Simd::<u8, $lanes>::load_select_ptr(needle.as_ptr().add(1), Mask::splat(true), Default::default());
@@ -104,10 +118,12 @@ macro_rules! avx_search_sub_1 {
 macro_rules! avx_search_padded {
     ($name:ident,$lanes:literal) => {
         /// # Safety
-        /// Haystack must be 64 bytes aligned and length must be >= 64 + needle.len() and divisible by 64.
+        /// Haystack must be 64 bytes aligned, length must be divisible by 64 and >= max(64, needle.len()),
+        /// and memory must be readable up to length + 64 + needle.len().
         /// you can use any u8 that not included in needle as padding.
         #[inline(never)]
-        unsafe fn $name(haystack: &[u8], needle: &[u8]) -> usize {
+        #[target_feature(enable = "avx512f,avx512bw")]
+        unsafe fn $name(haystack: &[u8], needle: &[u8], broadcast: &Broadcast) -> usize {
             const LEN_HALF:usize = $lanes / 2;
             let len = haystack.len();
             let end = haystack.as_ptr().add(len - needle.len());
@@ -121,7 +137,7 @@ macro_rules! avx_search_padded {
 
             let needle_low = std::simd::Simd::<u8, LEN_HALF>::load_select_ptr(needle.as_ptr(), Mask::splat(true), Default::default());
             let needle_high = std::simd::Simd::<u8, LEN_HALF>::load_select_ptr(needle.as_ptr().add(LEN_HALF), mask, Default::default());
-            let head = _mm512_set1_epi8(needle[0] as i8);
+            let head = load_broadcast(broadcast.head_ptr());
             loop {
                 let vector = _mm512_load_epi64(ptr);
                 let mut idx = _mm512_cmpeq_epi8_mask(vector, head);
@@ -177,121 +193,441 @@ avx_search_sub_1!(index_of65, 64);
 /// Perform O(n) search for needle length <= 65 bytes. this method is phantom, 
 /// and will remove and generate best search function for specific needle size.
 /// # Safety
-/// Haystack must be 64 bytes aligned and length must be >= 64 + needle.len() and divisible by 64.
+/// Haystack must be 64 bytes aligned, length must be divisible by 64 and >= max(64, needle.len()),
+/// and memory must be readable up to length + 64 + needle.len().
 /// you can use any u8 that not included in needle as padding.
 #[inline(always)]
 pub unsafe fn avx_search<const NEEDLE_SIZE: usize>(haystack: &[u8], needle: &[u8; NEEDLE_SIZE]) -> usize {
+    let broadcast = &Broadcast::new(needle);
     // compiler will choose best function for specific needle size
     if NEEDLE_SIZE > 65 {
-        // don't have yet :D
-        rt_search(haystack, needle)
+        index_of_long(haystack, needle, broadcast)
     } else if NEEDLE_SIZE == 65 {
-        index_of65(haystack, needle)
+        index_of65(haystack, needle, broadcast)
     } else if NEEDLE_SIZE == 64 {
-        index_of64(haystack, needle)
+        index_of64(haystack, needle, broadcast)
     } else if NEEDLE_SIZE > 33 {
-        index_of34_lt64(haystack, needle)
+        index_of34_lt64(haystack, needle, broadcast)
     } else if NEEDLE_SIZE == 33 {
-        index_of33(haystack, needle)
+        index_of33(haystack, needle, broadcast)
     } else if NEEDLE_SIZE == 32 {
-        index_of32(haystack, needle)
+        index_of32(haystack, needle, broadcast)
     } else if NEEDLE_SIZE > 17 {
-        index_of18_lt32(haystack, needle)
+        index_of18_lt32(haystack, needle, broadcast)
     } else if NEEDLE_SIZE == 17 {
-        index_of17(haystack, needle)
+        index_of17(haystack, needle, broadcast)
     } else if NEEDLE_SIZE == 16 {
-        index_of16(haystack, needle)
+        index_of16(haystack, needle, broadcast)
     } else if NEEDLE_SIZE > 9 {
-        index_of10_lt16(haystack, needle)
+        index_of10_lt16(haystack, needle, broadcast)
     } else if NEEDLE_SIZE == 9 {
-        index_of9(haystack, needle)
+        index_of9(haystack, needle, broadcast)
     } else if NEEDLE_SIZE == 8 {
-        index_of8(haystack, needle)
+        index_of8(haystack, needle, broadcast)
     } else if NEEDLE_SIZE > 5 {
-        index_of6_lt8(haystack, needle)
+        index_of6_lt8(haystack, needle, broadcast)
     } else if NEEDLE_SIZE == 5 {
-        index_of5(haystack, needle)
+        index_of5(haystack, needle, broadcast)
     } else if NEEDLE_SIZE == 4 {
-        index_of4(haystack, needle)
+        index_of4(haystack, needle, broadcast)
     } else if NEEDLE_SIZE == 3 {
-        index_of3(haystack, needle)
+        index_of3(haystack, needle, broadcast)
     } else if NEEDLE_SIZE == 2 {
-        index_of2(haystack, needle)
+        index_of2(haystack, needle).unwrap_or(haystack.len())
     } else if NEEDLE_SIZE == 1 {
-        index_of(haystack, needle[0])
+        index_of(haystack, needle[0]).unwrap_or(haystack.len())
     } else {
-        rt_search(haystack, needle)
+        rt_search(haystack, needle, broadcast)
     }
 }
 
-#[inline(never)]
-fn rt_search(haystack: &[u8], needle: &[u8]) -> usize {
-    unsafe { assert_unchecked(haystack.as_ptr().is_aligned_to(64)) };
-    memchr::memmem::find(haystack, needle).unwrap_or(haystack.len())
+/// Choose function from the set that [avx_search] would use for needle of `needle_len` bytes,
+/// the returned function has the same contract as [avx_search].
+pub(crate) fn kernel(needle_len: usize) -> Kernel {
+    match needle_len {
+        0 => rt_search,
+        1 => |haystack, needle, _| unsafe { index_of(haystack, needle[0]).unwrap_or(haystack.len()) },
+        2 => |haystack, needle, _| unsafe { index_of2(haystack, needle).unwrap_or(haystack.len()) },
+        3 => index_of3,
+        4 => index_of4,
+        5 => index_of5,
+        6..=7 => index_of6_lt8,
+        8 => index_of8,
+        9 => index_of9,
+        10..=15 => index_of10_lt16,
+        16 => index_of16,
+        17 => index_of17,
+        18..=31 => index_of18_lt32,
+        32 => index_of32,
+        33 => index_of33,
+        34..=63 => index_of34_lt64,
+        64 => index_of64,
+        65 => index_of65,
+        _ => index_of_long,
+    }
 }
 
+/// Filter candidate by first and last byte of needle, then compare whole needle 64 bytes at a time.
 /// # Safety
-/// Haystack must be 64 bytes aligned and length must be >= 64.
-pub unsafe fn index_of(haystack: &[u8], needle: u8) -> usize {
+/// Haystack must be 64 bytes aligned, length must be divisible by 64 and >= max(64, needle.len()),
+/// and memory must be readable up to length + 64 + needle.len().
+/// you can use any u8 that not included in needle as padding.
+#[inline(never)]
+#[target_feature(enable = "avx512f,avx512bw")]
+unsafe fn index_of_long(haystack: &[u8], needle: &[u8], broadcast: &Broadcast) -> usize {
     let len = haystack.len();
-    let end = haystack.as_ptr().add(len);
+    let end = haystack.as_ptr().add(len - needle.len());
     assert_unchecked(len >= 64);
-    let mut ptr = haystack.as_ptr() as *const i64;
-    assert_unchecked(ptr.is_aligned_to(64));
+    assert_unchecked(needle.len() > 65);
 
-    let head = _mm512_set1_epi8(needle as i8);
+    let last = needle.len() - 1;
+    let mut ptr = haystack.as_ptr();
+    assert_unchecked(ptr.is_aligned_to(64));
+    let head = load_broadcast(broadcast.head_ptr());
+    let tail = load_broadcast(broadcast.tail_ptr());
     loop {
-        // mark last element as 0 to avoid out of bounds
-        // let vector = (!mask_false(mask.to_bitmask().leading_zeros() as usize - 1)).select(vector, Simd::splat(0));
-        let vector = _mm512_load_epi64(ptr);
-        let idx = _mm512_cmpeq_epi8_mask(vector, head);
-        if idx != 0 {
+        // last byte is loaded unaligned from the same block shifted by needle length
+        let mut idx = _mm512_cmpeq_epi8_mask(_mm512_load_epi64(ptr.cast()), head) & _mm512_cmpeq_epi8_mask(_mm512_loadu_epi8(ptr.add(last).cast()), tail);
+        while idx != 0 {
             let occurrence = idx.trailing_zeros() as usize;
-            return (ptr.addr() - haystack.as_ptr().addr()) + occurrence;
+            let chunk_ptr = ptr.add(occurrence);
+            if equal_long(chunk_ptr, needle) {
+                return chunk_ptr.addr() - haystack.as_ptr().addr();
+            }
+            // idx ^= 1 << occurrence;
+            asm!("btc {},{}", inout(reg) idx, in(reg) occurrence);
         }
 
         asm!("add {},64", inout(reg) ptr);
-        if ptr.cast() > end {
+        if ptr > end {
             break;
         }
     }
     len
 }
 
+#[inline(never)]
+fn rt_search(haystack: &[u8], needle: &[u8], _: &Broadcast) -> usize {
+    unsafe { assert_unchecked(haystack.as_ptr().is_aligned_to(64)) };
+    memchr::memmem::find(haystack, needle).unwrap_or(haystack.len())
+}
+
+/// Find first `needle` in `haystack`, bytes after `haystack.len()` are never reported.
+/// # Safety
+/// Haystack must be 64 bytes aligned and readable up to `haystack.len()` rounded up to 64.
+#[target_feature(enable = "avx512f,avx512bw")]
+pub unsafe fn index_of(haystack: &[u8], needle: u8) -> Option<usize> {
+    let len = haystack.len();
+    let ptr = haystack.as_ptr() as *const i64;
+    assert_unchecked(ptr.is_aligned_to(64));
+
+    let head = _mm512_set1_epi8(needle as i8);
+    let mut offset = 0;
+    while offset < len {
+        let vector = _mm512_load_epi64(ptr.byte_add(offset));
+        let idx = _mm512_cmpeq_epi8_mask(vector, head) & tail_mask(len - offset);
+        if idx != 0 {
+            return Some(offset + idx.trailing_zeros() as usize);
+        }
+        offset += 64;
+    }
+    None
+}
+
 // last bit
-const CARRY_MASK: __mmask64 = 1 << 63;
+const CARRY_SHIFT: u32 = 63;
 
+/// Find first `needle` in `haystack`, bytes after `haystack.len()` are never reported.
 /// # Safety
-/// Haystack must be 64 bytes aligned and length must be >= 64.
+/// Haystack must be 64 bytes aligned and readable up to `haystack.len()` rounded up to 64.
 #[inline(never)]
-pub unsafe fn index_of2(haystack: &[u8], needle: &[u8]) -> usize {
+#[target_feature(enable = "avx512f,avx512bw")]
+pub unsafe fn index_of2(haystack: &[u8], needle: &[u8]) -> Option<usize> {
     let len = haystack.len();
-    let end = haystack.as_ptr().add(len);
-    assert_unchecked(len >= 64);
-    let mut ptr = haystack.as_ptr() as *const i64;
+    let ptr = haystack.as_ptr() as *const i64;
     assert_unchecked(ptr.is_aligned_to(64));
     assert_unchecked(needle.len() == 2);
     let mut carry: __mmask64 = 0;
 
     let head = _mm512_set1_epi8(needle[0] as i8);
     let tail = _mm512_set1_epi8(needle[1] as i8);
-    loop {
-        let vector = _mm512_load_epi64(ptr);
+    let mut offset = 0;
+    while offset < len {
+        let vector = _mm512_load_epi64(ptr.byte_add(offset));
         let idx = _mm512_cmpeq_epi8_mask(vector, head);
-        let mask = _kshiftli_mask64::<1>(idx) | carry;
-        carry = if idx & CARRY_MASK == 0 { 0 } else { 1 };
-        let cmp = _mm512_mask_cmpeq_epi8_mask(mask, vector, tail);
-        let idx = mask & cmp;
+        // position of second byte, head at last lane of previous block is carried to first lane
+        let mask = (_kshiftli_mask64::<1>(idx) | carry) & tail_mask(len - offset);
+        carry = idx >> CARRY_SHIFT;
+        let idx = _mm512_mask_cmpeq_epi8_mask(mask, vector, tail);
         if idx != 0 {
-            let occurrence = idx.trailing_zeros() as usize;
+            // carry only set after first block, so offset is never 0 when second byte is at first lane
+            return Some(offset + idx.trailing_zeros() as usize - 1);
+        }
+        offset += 64;
+    }
+    None
+}
 
-            return (ptr.addr() - haystack.as_ptr().addr()) + (occurrence - 1);
+/// Find last `needle` in `haystack` scanning 64 bytes blocks backward, bytes after `haystack.len()` are never reported.
+/// # Safety
+/// Haystack must be 64 bytes aligned and readable up to `haystack.len()` rounded up to 64.
+#[inline(never)]
+#[target_feature(enable = "avx512f,avx512bw")]
+pub unsafe fn rindex_of(haystack: &[u8], needle: u8) -> Option<usize> {
+    let len = haystack.len();
+    let ptr = haystack.as_ptr() as *const i64;
+    assert_unchecked(ptr.is_aligned_to(64));
+
+    let head = _mm512_set1_epi8(needle as i8);
+    let mut offset = len.next_multiple_of(64);
+    while offset > 0 {
+        offset -= 64;
+        let vector = _mm512_load_epi64(ptr.byte_add(offset));
+        let idx = _mm512_cmpeq_epi8_mask(vector, head) & tail_mask(len - offset);
+        if idx != 0 {
+            return Some(offset + 63 - idx.leading_zeros() as usize);
         }
+    }
+    None
+}
 
-        asm!("add {},64", inout(reg) ptr);
-        if ptr.cast() > end {
+/// Find last `needle` in `haystack`, candidate is filtered by first byte from the last block
+/// so only bytes inside `haystack.len()` are compared.
+/// # Safety
+/// Haystack must be 64 bytes aligned and readable up to `haystack.len()` rounded up to 64.
+#[inline(never)]
+#[target_feature(enable = "avx512f,avx512bw")]
+unsafe fn rindex_of_n(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    let ptr = haystack.as_ptr() as *const i64;
+    assert_unchecked(ptr.is_aligned_to(64));
+    assert_unchecked(needle.len() >= 2 && needle.len() <= haystack.len());
+    // last start position where whole needle is inside haystack
+    let last = haystack.len() - needle.len();
+
+    let head = _mm512_set1_epi8(needle[0] as i8);
+    let mut offset = (last + 1).next_multiple_of(64);
+    while offset > 0 {
+        offset -= 64;
+        let vector = _mm512_load_epi64(ptr.byte_add(offset));
+        let mut idx = _mm512_cmpeq_epi8_mask(vector, head) & tail_mask(last + 1 - offset);
+        while idx != 0 {
+            let occurrence = 63 - idx.leading_zeros() as usize;
+            if haystack.get_unchecked(offset + occurrence..offset + occurrence + needle.len()) == needle {
+                return Some(offset + occurrence);
+            }
+            idx ^= 1 << occurrence;
+        }
+    }
+    None
+}
+
+/// Reverse counterpart of [avx_search], return start of last occurrence of `needle`.
+/// # Safety
+/// Haystack must be 64 bytes aligned and readable up to `haystack.len()` rounded up to 64.
+#[inline(always)]
+pub unsafe fn avx_rfind<const NEEDLE_SIZE: usize>(haystack: &[u8], needle: &[u8; NEEDLE_SIZE]) -> Option<usize> {
+    match NEEDLE_SIZE {
+        0 => Some(haystack.len()),
+        1 => rindex_of(haystack, needle[0]),
+        _ if NEEDLE_SIZE > haystack.len() => None,
+        _ => rindex_of_n(haystack, needle),
+    }
+}
+
+/// Nibble tables of [ByteSet] broadcast to every 128 bits lane, `vpshufb` lookup inside each lane.
+struct SetTables {
+    low: __m512i,
+    high: __m512i,
+    bit: __m512i,
+}
+
+impl SetTables {
+    #[inline(always)]
+    unsafe fn new(set: &ByteSet) -> Self {
+        Self {
+            low: _mm512_broadcast_i32x4(_mm_loadu_si128(set.low.as_ptr().cast())),
+            high: _mm512_broadcast_i32x4(_mm_loadu_si128(set.high.as_ptr().cast())),
+            bit: _mm512_broadcast_i32x4(_mm_loadu_si128(NIBBLE_BIT.as_ptr().cast())),
+        }
+    }
+
+    #[inline(always)]
+    unsafe fn mask(&self, vector: __m512i) -> u64 {
+        // vpshufb return 0 for index with highest bit set, so each byte only hit one of the tables
+        let row = _mm512_or_si512(
+            _mm512_shuffle_epi8(self.low, vector),
+            _mm512_shuffle_epi8(self.high, _mm512_xor_si512(vector, _mm512_set1_epi8(i8::MIN))),
+        );
+        let high_nibble = _mm512_and_si512(_mm512_srli_epi16::<4>(vector), _mm512_set1_epi8(0x0f));
+        _mm512_test_epi8_mask(row, _mm512_shuffle_epi8(self.bit, high_nibble))
+    }
+}
+
+/// Return bitmask of bytes in `set` among 64 bytes start at `ptr`.
+/// # Safety
+/// `ptr` must be readable for 64 bytes.
+#[inline]
+#[target_feature(enable = "avx512f,avx512bw")]
+pub unsafe fn byte_set_mask(ptr: *const u8, set: &ByteSet) -> u64 {
+    SetTables::new(set).mask(_mm512_loadu_epi8(ptr.cast()))
+}
+
+/// Find first byte of `haystack` that is in `set`, bytes after `haystack.len()` are never reported.
+/// # Safety
+/// Haystack must be 64 bytes aligned and readable up to `haystack.len()` rounded up to 64.
+#[inline(never)]
+#[target_feature(enable = "avx512f,avx512bw")]
+pub unsafe fn index_of_set(haystack: &[u8], set: &ByteSet) -> Option<usize> {
+    let len = haystack.len();
+    let ptr = haystack.as_ptr() as *const i64;
+    assert_unchecked(ptr.is_aligned_to(64));
+
+    let tables = SetTables::new(set);
+    let mut offset = 0;
+    while offset < len {
+        let idx = tables.mask(_mm512_load_epi64(ptr.byte_add(offset))) & tail_mask(len - offset);
+        if idx != 0 {
+            return Some(offset + idx.trailing_zeros() as usize);
+        }
+        offset += 64;
+    }
+    None
+}
+
+/// Return bitmask of bytes equal to `needle` among 64 bytes start at `ptr`.
+/// # Safety
+/// `ptr` must be readable for 64 bytes.
+#[inline]
+#[target_feature(enable = "avx512f,avx512bw")]
+pub unsafe fn byte_mask(ptr: *const u8, needle: u8) -> u64 {
+    _mm512_cmpeq_epi8_mask(_mm512_loadu_epi8(ptr.cast()), _mm512_set1_epi8(needle as i8))
+}
+
+/// Return bitmask of positions among 64 bytes start at `ptr` that equal first byte of needle
+/// and whose byte `last` positions later equal last byte, same filter as [index_of_long].
+/// # Safety
+/// `ptr` must be readable for `64 + last` bytes.
+#[inline]
+#[target_feature(enable = "avx512f,avx512bw")]
+pub unsafe fn byte_pair_mask(ptr: *const u8, broadcast: &Broadcast, last: usize) -> u64 {
+    _mm512_cmpeq_epi8_mask(_mm512_loadu_epi8(ptr.cast()), load_broadcast(broadcast.head_ptr()))
+        & _mm512_cmpeq_epi8_mask(_mm512_loadu_epi8(ptr.add(last).cast()), load_broadcast(broadcast.tail_ptr()))
+}
+
+/// Lowercase ASCII letters of 64 bytes vector.
+#[inline(always)]
+unsafe fn lowercase(vector: __m512i) -> __m512i {
+    let upper = _mm512_cmplt_epu8_mask(_mm512_sub_epi8(vector, _mm512_set1_epi8(b'A' as i8)), _mm512_set1_epi8(26));
+    _mm512_mask_add_epi8(vector, upper, vector, _mm512_set1_epi8(0x20))
+}
+
+macro_rules! avx_search_ignore_case {
+    ($name:ident,$lanes:literal) => {
+        /// Same as [avx_search] but haystack is lowercased on the fly, `needle` must be lowercase.
+        /// # Safety
+        /// Haystack must be 64 bytes aligned, length must be divisible by 64 and >= max(64, needle.len()),
+        /// and memory must be readable up to length + 64 + needle.len().
+        #[inline(never)]
+        #[target_feature(enable = "avx512f,avx512bw")]
+        unsafe fn $name(haystack: &[u8], needle: &[u8], broadcast: &Broadcast) -> usize {
+            let len = haystack.len();
+            let end = haystack.as_ptr().add(len - needle.len());
+            assert_unchecked(len >= 64);
+            assert_unchecked(!needle.is_empty() && needle.len() <= $lanes);
+
+            let mut ptr = haystack.as_ptr();
+            assert_unchecked(ptr.is_aligned_to(64));
+            let mask = Mask::<i8, $lanes>::from_bitmask(tail_mask(needle.len()));
+            let needle_vector = std::simd::Simd::<u8, $lanes>::load_select_ptr(needle.as_ptr(), mask, Default::default());
+            let head = load_broadcast(broadcast.head_ptr());
+            loop {
+                let mut idx = _mm512_cmpeq_epi8_mask(lowercase(_mm512_load_epi64(ptr.cast())), head);
+                while idx != 0 {
+                    let occurrence = idx.trailing_zeros() as usize;
+                    let chunk = std::simd::Simd::<u8, $lanes>::load_select_ptr(ptr.add(occurrence), mask, Default::default());
+                    if simd_lowercase(chunk) == needle_vector {
+                        return ptr.add(occurrence).addr() - haystack.as_ptr().addr();
+                    }
+                    idx &= idx - 1;
+                }
+
+                ptr = ptr.add(64);
+                if ptr > end {
+                    break;
+                }
+            }
+            len
+        }
+    };
+}
+
+// needle.len() <= 8
+avx_search_ignore_case!(index_of_ignore_case_le8, 8);
+// needle.len() > 8 && needle.len() <= 16
+avx_search_ignore_case!(index_of_ignore_case_le16, 16);
+// needle.len() > 16 && needle.len() <= 32
+avx_search_ignore_case!(index_of_ignore_case_le32, 32);
+// needle.len() > 32 && needle.len() <= 64
+avx_search_ignore_case!(index_of_ignore_case_le64, 64);
+
+/// Same as [index_of_long] but haystack is lowercased on the fly, `needle` must be lowercase.
+/// # Safety
+/// Haystack must be 64 bytes aligned, length must be divisible by 64 and >= max(64, needle.len()),
+/// and memory must be readable up to length + 64 + needle.len().
+#[inline(never)]
+#[target_feature(enable = "avx512f,avx512bw")]
+unsafe fn index_of_long_ignore_case(haystack: &[u8], needle: &[u8], broadcast: &Broadcast) -> usize {
+    let len = haystack.len();
+    let end = haystack.as_ptr().add(len - needle.len());
+    assert_unchecked(len >= 64);
+    assert_unchecked(needle.len() > 64);
+
+    let last = needle.len() - 1;
+    let mut ptr = haystack.as_ptr();
+    assert_unchecked(ptr.is_aligned_to(64));
+    let head = load_broadcast(broadcast.head_ptr());
+    let tail = load_broadcast(broadcast.tail_ptr());
+    loop {
+        let mut idx = _mm512_cmpeq_epi8_mask(lowercase(_mm512_load_epi64(ptr.cast())), head)
+            & _mm512_cmpeq_epi8_mask(lowercase(_mm512_loadu_epi8(ptr.add(last).cast())), tail);
+        while idx != 0 {
+            let occurrence = idx.trailing_zeros() as usize;
+            if equal_long_ignore_case(ptr.add(occurrence), needle) {
+                return ptr.add(occurrence).addr() - haystack.as_ptr().addr();
+            }
+            idx &= idx - 1;
+        }
+
+        ptr = ptr.add(64);
+        if ptr > end {
             break;
         }
     }
     len
-}
\ No newline at end of file
+}
+
+/// Choose case-insensitive search function for needle of `needle_len` bytes,
+/// the returned function has the same contract as [avx_search] and expect lowercase needle.
+pub(crate) fn kernel_ignore_case(needle_len: usize) -> Kernel {
+    match needle_len {
+        0 => |_, _, _| 0,
+        1..=8 => index_of_ignore_case_le8,
+        9..=16 => index_of_ignore_case_le16,
+        17..=32 => index_of_ignore_case_le32,
+        33..=64 => index_of_ignore_case_le64,
+        _ => index_of_long_ignore_case,
+    }
+}
+
+/// Same as [avx_search] but match ASCII letters ignoring case, e.g. `chunked` matches `Chunked`.
+/// # Safety
+/// Haystack must be 64 bytes aligned, length must be divisible by 64 and >= max(64, needle.len()),
+/// and memory must be readable up to length + 64 + needle.len().
+/// you can use any u8 that not included in needle as padding.
+#[inline(always)]
+pub unsafe fn avx_search_ignore_ascii_case<const NEEDLE_SIZE: usize>(haystack: &[u8], needle: &[u8; NEEDLE_SIZE]) -> usize {
+    let mut lower = *needle;
+    lower.make_ascii_lowercase();
+    // needle size is known at compile time, so the kernel is chosen by the compiler
+    kernel_ignore_case(NEEDLE_SIZE)(haystack, &lower, &Broadcast::new(&lower))
+}
@@ -0,0 +1,635 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[cfg(target_arch = "x86_64")]
+use crate::utils::{avx, avx2};
+#[cfg(target_arch = "aarch64")]
+use crate::utils::neon;
+use crate::utils::simd;
+use crate::utils::simd::byte_set::ByteSet;
+use crate::utils::simd::search::Broadcast;
+
+/// Instruction set used by dispatched functions, ordered from slowest to fastest within same architecture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum Isa {
+    /// Portable SIMD compiled for target baseline.
+    Portable = 1,
+    /// Portable SIMD recompiled for x86-64-v2 (up to SSE4.2), there is no hand written 128 bits kernel.
+    X86V2 = 2,
+    Avx2 = 3,
+    Avx512 = 4,
+    Neon = 5,
+}
+
+const UNKNOWN: u8 = 0;
+static ISA: AtomicU8 = AtomicU8::new(UNKNOWN);
+
+impl Isa {
+    #[inline]
+    const fn from_u8(value: u8) -> Self {
+        match value {
+            2 => Isa::X86V2,
+            3 => Isa::Avx2,
+            4 => Isa::Avx512,
+            5 => Isa::Neon,
+            _ => Isa::Portable,
+        }
+    }
+}
+
+/// Detect best instruction set supported by current CPU, prefer [isa] which cache the result.
+pub fn detect() -> Isa {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx512f") && is_x86_feature_detected!("avx512bw") {
+            return Isa::Avx512;
+        }
+        if is_x86_feature_detected!("avx2") {
+            return Isa::Avx2;
+        }
+        if is_x86_feature_detected!("sse4.2") && is_x86_feature_detected!("popcnt") {
+            return Isa::X86V2;
+        }
+    }
+    #[cfg(target_arch = "aarch64")]
+    if std::arch::is_aarch64_feature_detected!("neon") {
+        return Isa::Neon;
+    }
+    Isa::Portable
+}
+
+/// Instruction set selected for current CPU, detected once on first call.
+#[inline]
+pub fn isa() -> Isa {
+    let value = ISA.load(Ordering::Relaxed);
+    if value != UNKNOWN {
+        return Isa::from_u8(value);
+    }
+    let isa = detect();
+    ISA.store(isa as u8, Ordering::Relaxed);
+    isa
+}
+
+/// Search function for needle known only at runtime, same contract as [search].
+/// [Broadcast] must be built from the needle, so it can be reused across haystacks.
+pub(crate) type Kernel = unsafe fn(&[u8], &[u8], &Broadcast) -> usize;
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.2,popcnt")]
+unsafe fn search_x86v2(haystack: &[u8], needle: &[u8], broadcast: &Broadcast) -> usize {
+    simd::search::search(haystack, needle, broadcast)
+}
+
+#[inline(never)]
+unsafe fn search_portable(haystack: &[u8], needle: &[u8], broadcast: &Broadcast) -> usize {
+    simd::search::search(haystack, needle, broadcast)
+}
+
+/// Search with specific instruction set, caller must ensure CPU support it.
+#[inline(always)]
+pub(crate) unsafe fn search_with<const NEEDLE_SIZE: usize>(isa: Isa, haystack: &[u8], needle: &[u8; NEEDLE_SIZE]) -> usize {
+    match isa {
+        #[cfg(target_arch = "x86_64")]
+        Isa::Avx512 => avx::search::avx_search(haystack, needle),
+        #[cfg(target_arch = "x86_64")]
+        Isa::Avx2 => avx2::search::avx2_search(haystack, needle),
+        #[cfg(target_arch = "x86_64")]
+        Isa::X86V2 => search_x86v2(haystack, needle, &Broadcast::new(needle)),
+        #[cfg(target_arch = "aarch64")]
+        Isa::Neon => neon::search::neon_search(haystack, needle),
+        _ => search_portable(haystack, needle, &Broadcast::new(needle)),
+    }
+}
+
+/// Choose search function for needle of `needle_len` bytes with specific instruction set,
+/// caller must ensure CPU support it before calling the returned function.
+pub(crate) fn kernel(isa: Isa, needle_len: usize) -> Kernel {
+    match isa {
+        #[cfg(target_arch = "x86_64")]
+        Isa::Avx512 => avx::search::kernel(needle_len),
+        #[cfg(target_arch = "x86_64")]
+        Isa::Avx2 => avx2::search::kernel(needle_len),
+        #[cfg(target_arch = "x86_64")]
+        Isa::X86V2 => search_x86v2,
+        #[cfg(target_arch = "aarch64")]
+        Isa::Neon => neon::search::kernel(needle_len),
+        _ => search_portable,
+    }
+}
+
+/// Same as [avx::search::avx_search] but use best instruction set of current CPU.
+/// # Safety
+/// Haystack must be 64 bytes aligned, length must be divisible by 64 and >= max(64, needle.len()),
+/// and memory must be readable up to length + 64 + needle.len().
+/// you can use any u8 that not included in needle as padding.
+#[inline(always)]
+pub unsafe fn search<const NEEDLE_SIZE: usize>(haystack: &[u8], needle: &[u8; NEEDLE_SIZE]) -> usize {
+    search_with(isa(), haystack, needle)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.2,popcnt")]
+unsafe fn search_ignore_case_x86v2(haystack: &[u8], needle: &[u8], broadcast: &Broadcast) -> usize {
+    simd::search::search_ignore_case(haystack, needle, broadcast)
+}
+
+#[inline(never)]
+unsafe fn search_ignore_case_portable(haystack: &[u8], needle: &[u8], broadcast: &Broadcast) -> usize {
+    simd::search::search_ignore_case(haystack, needle, broadcast)
+}
+
+/// Choose case-insensitive search function for needle of `needle_len` bytes with specific instruction set,
+/// the returned function has the same contract as [search] and expect lowercase needle.
+pub(crate) fn kernel_ignore_case(isa: Isa, needle_len: usize) -> Kernel {
+    match isa {
+        #[cfg(target_arch = "x86_64")]
+        Isa::Avx512 => avx::search::kernel_ignore_case(needle_len),
+        #[cfg(target_arch = "x86_64")]
+        Isa::Avx2 => avx2::search::kernel_ignore_case(needle_len),
+        #[cfg(target_arch = "x86_64")]
+        Isa::X86V2 => search_ignore_case_x86v2,
+        #[cfg(target_arch = "aarch64")]
+        Isa::Neon => neon::search::kernel_ignore_case(needle_len),
+        _ => search_ignore_case_portable,
+    }
+}
+
+/// Same as [avx::search::avx_search_ignore_ascii_case] but use best instruction set of current CPU.
+/// # Safety
+/// Haystack must be 64 bytes aligned, length must be divisible by 64 and >= max(64, needle.len()),
+/// and memory must be readable up to length + 64 + needle.len().
+#[inline(always)]
+pub unsafe fn search_ignore_ascii_case<const NEEDLE_SIZE: usize>(haystack: &[u8], needle: &[u8; NEEDLE_SIZE]) -> usize {
+    let mut lower = *needle;
+    lower.make_ascii_lowercase();
+    kernel_ignore_case(isa(), NEEDLE_SIZE)(haystack, &lower, &Broadcast::new(&lower))
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.2,popcnt")]
+unsafe fn index_of_x86v2(haystack: &[u8], needle: u8) -> Option<usize> {
+    simd::search::index_of(haystack, needle)
+}
+
+#[inline(never)]
+unsafe fn index_of_portable(haystack: &[u8], needle: u8) -> Option<usize> {
+    simd::search::index_of(haystack, needle)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.2,popcnt")]
+unsafe fn index_of2_x86v2(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    simd::search::index_of2(haystack, needle)
+}
+
+#[inline(never)]
+unsafe fn index_of2_portable(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    simd::search::index_of2(haystack, needle)
+}
+
+/// [index_of] with specific instruction set, caller must ensure CPU support it.
+#[inline(always)]
+pub(crate) unsafe fn index_of_with(isa: Isa, haystack: &[u8], needle: u8) -> Option<usize> {
+    match isa {
+        #[cfg(target_arch = "x86_64")]
+        Isa::Avx512 => avx::search::index_of(haystack, needle),
+        #[cfg(target_arch = "x86_64")]
+        Isa::Avx2 => avx2::search::index_of(haystack, needle),
+        #[cfg(target_arch = "x86_64")]
+        Isa::X86V2 => index_of_x86v2(haystack, needle),
+        #[cfg(target_arch = "aarch64")]
+        Isa::Neon => neon::search::index_of(haystack, needle),
+        _ => index_of_portable(haystack, needle),
+    }
+}
+
+/// [index_of2] with specific instruction set, caller must ensure CPU support it.
+#[inline(always)]
+pub(crate) unsafe fn index_of2_with(isa: Isa, haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    match isa {
+        #[cfg(target_arch = "x86_64")]
+        Isa::Avx512 => avx::search::index_of2(haystack, needle),
+        #[cfg(target_arch = "x86_64")]
+        Isa::Avx2 => avx2::search::index_of2(haystack, needle),
+        #[cfg(target_arch = "x86_64")]
+        Isa::X86V2 => index_of2_x86v2(haystack, needle),
+        #[cfg(target_arch = "aarch64")]
+        Isa::Neon => neon::search::index_of2(haystack, needle),
+        _ => index_of2_portable(haystack, needle),
+    }
+}
+
+/// Same as [avx::search::index_of] but use best instruction set of current CPU.
+/// # Safety
+/// Haystack must be 64 bytes aligned and readable up to `haystack.len()` rounded up to 64.
+#[inline(always)]
+pub unsafe fn index_of(haystack: &[u8], needle: u8) -> Option<usize> {
+    index_of_with(isa(), haystack, needle)
+}
+
+/// Same as [avx::search::index_of2] but use best instruction set of current CPU.
+/// # Safety
+/// Haystack must be 64 bytes aligned and readable up to `haystack.len()` rounded up to 64.
+#[inline(always)]
+pub unsafe fn index_of2(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    index_of2_with(isa(), haystack, needle)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.2,popcnt")]
+unsafe fn rindex_of_x86v2(haystack: &[u8], needle: u8) -> Option<usize> {
+    simd::search::rindex_of(haystack, needle)
+}
+
+#[inline(never)]
+unsafe fn rindex_of_portable(haystack: &[u8], needle: u8) -> Option<usize> {
+    simd::search::rindex_of(haystack, needle)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.2,popcnt")]
+unsafe fn rfind_x86v2(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    simd::search::rfind(haystack, needle)
+}
+
+#[inline(never)]
+unsafe fn rfind_portable(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    simd::search::rfind(haystack, needle)
+}
+
+/// [rindex_of] with specific instruction set, caller must ensure CPU support it.
+#[inline(always)]
+pub(crate) unsafe fn rindex_of_with(isa: Isa, haystack: &[u8], needle: u8) -> Option<usize> {
+    match isa {
+        #[cfg(target_arch = "x86_64")]
+        Isa::Avx512 => avx::search::rindex_of(haystack, needle),
+        #[cfg(target_arch = "x86_64")]
+        Isa::Avx2 => avx2::search::rindex_of(haystack, needle),
+        #[cfg(target_arch = "x86_64")]
+        Isa::X86V2 => rindex_of_x86v2(haystack, needle),
+        #[cfg(target_arch = "aarch64")]
+        Isa::Neon => neon::search::rindex_of(haystack, needle),
+        _ => rindex_of_portable(haystack, needle),
+    }
+}
+
+/// [rfind] with specific instruction set, caller must ensure CPU support it.
+#[inline(always)]
+pub(crate) unsafe fn rfind_with<const NEEDLE_SIZE: usize>(isa: Isa, haystack: &[u8], needle: &[u8; NEEDLE_SIZE]) -> Option<usize> {
+    match isa {
+        #[cfg(target_arch = "x86_64")]
+        Isa::Avx512 => avx::search::avx_rfind(haystack, needle),
+        #[cfg(target_arch = "x86_64")]
+        Isa::Avx2 => avx2::search::avx2_rfind(haystack, needle),
+        #[cfg(target_arch = "x86_64")]
+        Isa::X86V2 => rfind_x86v2(haystack, needle),
+        #[cfg(target_arch = "aarch64")]
+        Isa::Neon => neon::search::neon_rfind(haystack, needle),
+        _ => rfind_portable(haystack, needle),
+    }
+}
+
+/// Same as [avx::search::rindex_of] but use best instruction set of current CPU.
+/// # Safety
+/// Haystack must be 64 bytes aligned and readable up to `haystack.len()` rounded up to 64.
+#[inline(always)]
+pub unsafe fn rindex_of(haystack: &[u8], needle: u8) -> Option<usize> {
+    rindex_of_with(isa(), haystack, needle)
+}
+
+/// Same as [avx::search::avx_rfind] but use best instruction set of current CPU.
+/// # Safety
+/// Haystack must be 64 bytes aligned and readable up to `haystack.len()` rounded up to 64.
+#[inline(always)]
+pub unsafe fn rfind<const NEEDLE_SIZE: usize>(haystack: &[u8], needle: &[u8; NEEDLE_SIZE]) -> Option<usize> {
+    rfind_with(isa(), haystack, needle)
+}
+
+/// Compare 64 bytes block with single byte, same contract as [byte_mask].
+pub(crate) type EqMask = unsafe fn(*const u8, u8) -> u64;
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.2,popcnt")]
+unsafe fn byte_mask_x86v2(ptr: *const u8, needle: u8) -> u64 {
+    simd::search::byte_mask(ptr, needle)
+}
+
+#[inline(never)]
+unsafe fn byte_mask_portable(ptr: *const u8, needle: u8) -> u64 {
+    simd::search::byte_mask(ptr, needle)
+}
+
+/// Choose block comparator with specific instruction set,
+/// caller must ensure CPU support it before calling the returned function.
+pub(crate) fn eq_mask(isa: Isa) -> EqMask {
+    match isa {
+        #[cfg(target_arch = "x86_64")]
+        Isa::Avx512 => avx::search::byte_mask,
+        #[cfg(target_arch = "x86_64")]
+        Isa::Avx2 => avx2::search::byte_mask,
+        #[cfg(target_arch = "x86_64")]
+        Isa::X86V2 => byte_mask_x86v2,
+        #[cfg(target_arch = "aarch64")]
+        Isa::Neon => neon::search::byte_mask,
+        _ => byte_mask_portable,
+    }
+}
+
+/// Same as [avx::search::byte_mask] but use best instruction set of current CPU.
+/// # Safety
+/// `ptr` must be readable for 64 bytes.
+#[inline(always)]
+pub unsafe fn byte_mask(ptr: *const u8, needle: u8) -> u64 {
+    eq_mask(isa())(ptr, needle)
+}
+
+/// Filter 64 bytes block by first and last byte of needle, same contract as [avx::search::byte_pair_mask].
+pub(crate) type PairMask = unsafe fn(*const u8, &Broadcast, usize) -> u64;
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.2,popcnt")]
+unsafe fn byte_pair_mask_x86v2(ptr: *const u8, broadcast: &Broadcast, last: usize) -> u64 {
+    simd::search::byte_pair_mask(ptr, broadcast, last)
+}
+
+#[inline(never)]
+unsafe fn byte_pair_mask_portable(ptr: *const u8, broadcast: &Broadcast, last: usize) -> u64 {
+    simd::search::byte_pair_mask(ptr, broadcast, last)
+}
+
+/// Choose first and last byte filter of the search kernels with specific instruction set,
+/// caller must ensure CPU support it before calling the returned function.
+pub(crate) fn pair_mask(isa: Isa) -> PairMask {
+    match isa {
+        #[cfg(target_arch = "x86_64")]
+        Isa::Avx512 => avx::search::byte_pair_mask,
+        #[cfg(target_arch = "x86_64")]
+        Isa::Avx2 => avx2::search::byte_pair_mask,
+        #[cfg(target_arch = "x86_64")]
+        Isa::X86V2 => byte_pair_mask_x86v2,
+        #[cfg(target_arch = "aarch64")]
+        Isa::Neon => neon::search::byte_pair_mask,
+        _ => byte_pair_mask_portable,
+    }
+}
+
+/// Classify 64 bytes block against [ByteSet], same contract as [byte_set_mask].
+pub(crate) type SetMask = unsafe fn(*const u8, &ByteSet) -> u64;
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.2,popcnt")]
+unsafe fn byte_set_mask_x86v2(ptr: *const u8, set: &ByteSet) -> u64 {
+    simd::byte_set::byte_set_mask(ptr, set)
+}
+
+#[inline(never)]
+unsafe fn byte_set_mask_portable(ptr: *const u8, set: &ByteSet) -> u64 {
+    simd::byte_set::byte_set_mask(ptr, set)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.2,popcnt")]
+unsafe fn index_of_set_x86v2(haystack: &[u8], set: &ByteSet) -> Option<usize> {
+    simd::byte_set::index_of_set(haystack, set)
+}
+
+#[inline(never)]
+unsafe fn index_of_set_portable(haystack: &[u8], set: &ByteSet) -> Option<usize> {
+    simd::byte_set::index_of_set(haystack, set)
+}
+
+/// Choose block classifier with specific instruction set,
+/// caller must ensure CPU support it before calling the returned function.
+pub(crate) fn set_mask(isa: Isa) -> SetMask {
+    match isa {
+        #[cfg(target_arch = "x86_64")]
+        Isa::Avx512 => avx::search::byte_set_mask,
+        #[cfg(target_arch = "x86_64")]
+        Isa::Avx2 => avx2::search::byte_set_mask,
+        #[cfg(target_arch = "x86_64")]
+        Isa::X86V2 => byte_set_mask_x86v2,
+        #[cfg(target_arch = "aarch64")]
+        Isa::Neon => neon::search::byte_set_mask,
+        _ => byte_set_mask_portable,
+    }
+}
+
+/// [index_of_set] with specific instruction set, caller must ensure CPU support it.
+#[inline(always)]
+pub(crate) unsafe fn index_of_set_with(isa: Isa, haystack: &[u8], set: &ByteSet) -> Option<usize> {
+    match isa {
+        #[cfg(target_arch = "x86_64")]
+        Isa::Avx512 => avx::search::index_of_set(haystack, set),
+        #[cfg(target_arch = "x86_64")]
+        Isa::Avx2 => avx2::search::index_of_set(haystack, set),
+        #[cfg(target_arch = "x86_64")]
+        Isa::X86V2 => index_of_set_x86v2(haystack, set),
+        #[cfg(target_arch = "aarch64")]
+        Isa::Neon => neon::search::index_of_set(haystack, set),
+        _ => index_of_set_portable(haystack, set),
+    }
+}
+
+/// Same as [avx::search::byte_set_mask] but use best instruction set of current CPU.
+/// # Safety
+/// `ptr` must be readable for 64 bytes.
+#[inline(always)]
+pub unsafe fn byte_set_mask(ptr: *const u8, set: &ByteSet) -> u64 {
+    set_mask(isa())(ptr, set)
+}
+
+/// Same as [avx::search::index_of_set] but use best instruction set of current CPU.
+/// # Safety
+/// Haystack must be 64 bytes aligned and readable up to `haystack.len()` rounded up to 64.
+#[inline(always)]
+pub unsafe fn index_of_set(haystack: &[u8], set: &ByteSet) -> Option<usize> {
+    index_of_set_with(isa(), haystack, set)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::{Buffer, BufferSlice};
+
+    const ALL: [Isa; 5] = [Isa::Portable, Isa::X86V2, Isa::Avx2, Isa::Avx512, Isa::Neon];
+
+    fn check<const N: usize>(haystack: &BufferSlice<4096, 4096>, needle: &[u8; N]) {
+        let expected = memchr::memmem::find(haystack, needle);
+        // search over whole block, padding is zero
+        let blocks = unsafe { std::slice::from_raw_parts(haystack.ptr(), haystack.len().next_multiple_of(64)) };
+        for isa in ALL.into_iter().filter(|&isa| isa <= detect()) {
+            let found = unsafe { search_with(isa, blocks, needle) };
+            assert_eq!(expected.unwrap_or(blocks.len()), found, "{isa:?} {:?}", std::str::from_utf8(needle));
+        }
+    }
+
+    #[test]
+    fn test_dispatch() {
+        assert_eq!(isa(), detect());
+        let haystack = BufferSlice::<4096, 4096>::from_slice(b"GET /index.html HTTP/1.1\r\nHost: developer.mozilla.org\r\nAccept-Language: fr\r\nUser-Agent: curl/7.64.1\r\n\r\n");
+        check(&haystack, b"G");
+        check(&haystack, b"\r\n");
+        check(&haystack, b"\r\n\r\n");
+        check(&haystack, b"Host");
+        check(&haystack, b"mozilla");
+        check(&haystack, b"developer.mozilla.org\r\nAccept-Language");
+        check(&haystack, b"X");
+        check(&haystack, b"missing");
+    }
+
+    #[test]
+    fn test_kernel() {
+        let haystack = BufferSlice::<4096, 4096>::from_slice(b"POST /upload HTTP/1.1\r\nHost: developer.mozilla.org\r\nUser-Agent: Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/108.0.0.0 Safari/537.36\r\nContent-Type: multipart/form-data; boundary=----WebKitFormBoundary7MA4YWxkTrZu0gW\r\n\r\n");
+        let blocks = unsafe { std::slice::from_raw_parts(haystack.ptr(), haystack.len().next_multiple_of(64)) };
+        for isa in ALL.into_iter().filter(|&isa| isa <= detect()) {
+            for needle_len in 0..=100 {
+                let needle = &haystack[haystack.len() - needle_len..];
+                let expected = memchr::memmem::find(&haystack, needle).unwrap();
+                assert_eq!(expected, unsafe { kernel(isa, needle_len)(blocks, needle, &Broadcast::new(needle)) }, "{isa:?} {needle_len}");
+            }
+        }
+    }
+
+    /// Padding after the end is filled with needle, so it is reported if kernel read past the end.
+    #[test]
+    fn test_index_of_property() {
+        let mut buffer = Buffer::<4096, 4096>::allocate();
+        let supported = ALL.into_iter().filter(|&isa| isa <= detect()).collect::<Vec<_>>();
+        for len in 0..=256 {
+            for pos in 0..=len {
+                let bytes = buffer.as_mut_slice();
+                bytes[..len].fill(b'.');
+                bytes[len..len + 128].fill(b'x');
+                if pos < len {
+                    bytes[pos] = b'x';
+                }
+                let haystack = &buffer.as_slice()[..len];
+                let expected = memchr::memchr(b'x', haystack);
+                for &isa in &supported {
+                    assert_eq!(expected, unsafe { index_of_with(isa, haystack, b'x') }, "{isa:?} len {len} pos {pos}");
+                }
+
+                let bytes = buffer.as_mut_slice();
+                bytes[..len].fill(b'.');
+                bytes[len..len + 128].fill(b'b');
+                // head at the last byte and tail in padding
+                if len > 0 {
+                    bytes[len - 1] = b'a';
+                }
+                if pos + 2 <= len {
+                    bytes[pos..pos + 2].copy_from_slice(b"ab");
+                }
+                let haystack = &buffer.as_slice()[..len];
+                let expected = memchr::memmem::find(haystack, b"ab");
+                for &isa in &supported {
+                    assert_eq!(expected, unsafe { index_of2_with(isa, haystack, b"ab") }, "{isa:?} len {len} pos {pos}");
+                }
+            }
+        }
+    }
+
+    /// Same as [test_index_of_property] but backward, needle is also placed in padding and before the match.
+    #[test]
+    fn test_rfind_property() {
+        let mut buffer = Buffer::<4096, 4096>::allocate();
+        let supported = ALL.into_iter().filter(|&isa| isa <= detect()).collect::<Vec<_>>();
+        for len in 0..=256 {
+            for pos in 0..=len {
+                let bytes = buffer.as_mut_slice();
+                bytes[..len].fill(b'.');
+                bytes[len..len + 128].fill(b'x');
+                if pos < len {
+                    bytes[pos] = b'x';
+                    bytes[pos / 2] = b'x';
+                }
+                let haystack = &buffer.as_slice()[..len];
+                let expected = memchr::memrchr(b'x', haystack);
+                for &isa in &supported {
+                    assert_eq!(expected, unsafe { rindex_of_with(isa, haystack, b'x') }, "{isa:?} len {len} pos {pos}");
+                }
+
+                let bytes = buffer.as_mut_slice();
+                bytes[..len].fill(b'.');
+                bytes[len..len + 128].fill(b'-');
+                // head near the end and rest of needle in padding
+                if len >= 2 {
+                    bytes[len - 2..len].copy_from_slice(b"--");
+                }
+                if pos + 4 <= len {
+                    bytes[pos..pos + 4].copy_from_slice(b"----");
+                    bytes[pos / 4..pos / 4 + 4].copy_from_slice(b"----");
+                }
+                let haystack = &buffer.as_slice()[..len];
+                let expected = memchr::memmem::rfind(haystack, b"----");
+                for &isa in &supported {
+                    assert_eq!(expected, unsafe { rfind_with(isa, haystack, b"----") }, "{isa:?} len {len} pos {pos}");
+                    assert_eq!(memchr::memmem::rfind(haystack, b"--"), unsafe { rfind_with(isa, haystack, b"--") }, "{isa:?} len {len} pos {pos}");
+                    assert_eq!(Some(len), unsafe { rfind_with(isa, haystack, b"") }, "{isa:?} len {len}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_index_of_set_property() {
+        let set = ByteSet::new(b"\r\n: \0\x7f\xff");
+        let mut buffer = Buffer::<4096, 4096>::allocate();
+        let supported = ALL.into_iter().filter(|&isa| isa <= detect()).collect::<Vec<_>>();
+        for len in 0..=256 {
+            for (pos, byte) in (0..=len).zip([b'\r', b'\n', b':', b' ', 0, 0x7f, 0xff].into_iter().cycle()) {
+                let bytes = buffer.as_mut_slice();
+                // every byte not in the set, and padding is in the set
+                bytes[..len].iter_mut().enumerate().for_each(|(i, b)| *b = b"az\x80\xfe\x01\t"[i % 6]);
+                bytes[len..len + 128].fill(b':');
+                if pos < len {
+                    bytes[pos] = byte;
+                }
+                let haystack = &buffer.as_slice()[..len];
+                let expected = haystack.iter().position(|&b| set.contains(b));
+                for &isa in &supported {
+                    assert_eq!(expected, unsafe { index_of_set_with(isa, haystack, &set) }, "{isa:?} len {len} pos {pos}");
+                }
+            }
+        }
+        let block: [u8; 64] = std::array::from_fn(|i| (i * 4) as u8 ^ 0x0d);
+        let expected = (0..64).filter(|&i| set.contains(block[i])).fold(0u64, |mask, i| mask | 1 << i);
+        let expected_eq = (0..64).filter(|&i| block[i] == b'\r').fold(0u64, |mask, i| mask | 1 << i);
+        let pair: [u8; 128] = std::array::from_fn(|i| b"-\r\n-"[i % 4]);
+        for isa in supported {
+            assert_eq!(expected, unsafe { set_mask(isa)(block.as_ptr(), &set) }, "{isa:?}");
+            assert_eq!(expected_eq, unsafe { eq_mask(isa)(block.as_ptr(), b'\r') }, "{isa:?}");
+            for (needle, expected) in [(b"\r-".as_slice(), 0), (b"\r\n", 0x2222_2222_2222_2222), (b"--", 0x8888_8888_8888_8888), (b"\r..-", 0x2222_2222_2222_2222)] {
+                let mask = unsafe { pair_mask(isa)(pair.as_ptr(), &Broadcast::new(needle), needle.len() - 1) };
+                assert_eq!(expected, mask, "{isa:?} {needle:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_kernel_ignore_case() {
+        let haystack = BufferSlice::<4096, 4096>::from_slice(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: gzip, CHUNKED\r\nConnection: Keep-Alive, Upgrade\r\nContent-Type: multipart/form-data; boundary=----WebKitFormBoundary7MA4YWxkTrZu0gW\r\n\r\n");
+        let blocks = unsafe { std::slice::from_raw_parts(haystack.ptr(), haystack.len().next_multiple_of(64)) };
+        let lower = haystack.to_ascii_lowercase();
+        for isa in ALL.into_iter().filter(|&isa| isa <= detect()) {
+            for needle_len in 0..=100 {
+                for from in [0, haystack.len() - needle_len] {
+                    let needle = &lower[from..from + needle_len];
+                    let expected = memchr::memmem::find(&lower, needle).unwrap();
+                    assert_eq!(expected, unsafe { kernel_ignore_case(isa, needle_len)(blocks, needle, &Broadcast::new(needle)) }, "{isa:?} {needle_len}");
+                }
+            }
+            let mut missing = *b"chunked!";
+            assert_eq!(blocks.len(), unsafe { kernel_ignore_case(isa, missing.len())(blocks, &missing, &Broadcast::new(&missing)) });
+            missing[7] = b'\r';
+            assert_eq!(42, unsafe { kernel_ignore_case(isa, missing.len())(blocks, &missing, &Broadcast::new(&missing)) });
+
+            // only A-Z are folded, neighbours and bytes with highest bit set are kept
+            let edge = BufferSlice::<4096, 4096>::from_slice(b"@[\xc1\xda`{Z");
+            let edge = unsafe { std::slice::from_raw_parts(edge.ptr(), 128) };
+            for (needle, expected) in [(b"`", 4), (b"{", 5), (b"z", 6), (b"\xe1", 128)] {
+                assert_eq!(expected, unsafe { kernel_ignore_case(isa, 1)(edge, needle, &Broadcast::new(needle)) }, "{isa:?} {needle:?}");
+            }
+        }
+        assert_eq!(63, unsafe { search_ignore_ascii_case(blocks, b"KEEP-alive") });
+    }
+}
@@ -1,4 +1,10 @@
 pub mod ascii;
 pub mod alloc;
 pub mod simd;
-pub mod avx;
\ No newline at end of file
+#[cfg(target_arch = "x86_64")]
+pub mod avx;
+#[cfg(target_arch = "x86_64")]
+pub mod avx2;
+#[cfg(target_arch = "aarch64")]
+pub mod neon;
+pub mod dispatch;
\ No newline at end of file
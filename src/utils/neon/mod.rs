@@ -0,0 +1,89 @@
+use std::arch::aarch64::{uint8x16_t, vandq_u8, vceqq_u8, vcgeq_u8, vcleq_u8, vcltq_u8, vdupq_n_u8, vgetq_lane_u64, vld1q_u8, vld1q_u8_x4, vorrq_u8, vpaddq_u8, vreinterpretq_u64_u8};
+
+pub mod search;
+
+/// Weight of each lane, used to collapse compare result into bitmask since NEON doesn't have movemask.
+static LANE_BIT: [u8; 16] = [1, 2, 4, 8, 16, 32, 64, 128, 1, 2, 4, 8, 16, 32, 64, 128];
+static LANE_INDEX: [u8; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+
+/// Compare 64 bytes start at `ptr` with `needle` and return bitmask of equal bytes.
+/// # Safety
+/// `ptr` must be readable for 64 bytes.
+#[inline(always)]
+pub unsafe fn cmpeq_mask(ptr: *const u8, needle: uint8x16_t) -> u64 {
+    let block = vld1q_u8_x4(ptr);
+    bitmask(vceqq_u8(block.0, needle), vceqq_u8(block.1, needle), vceqq_u8(block.2, needle), vceqq_u8(block.3, needle))
+}
+
+/// Same as [cmpeq_mask] but bytes are lowercased before compare.
+/// # Safety
+/// `ptr` must be readable for 64 bytes.
+#[inline(always)]
+pub unsafe fn cmpeq_mask_ignore_case(ptr: *const u8, needle: uint8x16_t) -> u64 {
+    let block = vld1q_u8_x4(ptr);
+    bitmask(
+        vceqq_u8(simd_lowercase(block.0), needle),
+        vceqq_u8(simd_lowercase(block.1), needle),
+        vceqq_u8(simd_lowercase(block.2), needle),
+        vceqq_u8(simd_lowercase(block.3), needle),
+    )
+}
+
+/// Collapse 4 compare results of 16 lanes (all bits set or zero) into bitmask of 64 lanes.
+#[inline(always)]
+pub unsafe fn bitmask(c0: uint8x16_t, c1: uint8x16_t, c2: uint8x16_t, c3: uint8x16_t) -> u64 {
+    let bit = vld1q_u8(LANE_BIT.as_ptr());
+    let b0 = vandq_u8(c0, bit);
+    let b1 = vandq_u8(c1, bit);
+    let b2 = vandq_u8(c2, bit);
+    let b3 = vandq_u8(c3, bit);
+    // every pairwise add halve the lanes, after 3 rounds each byte contain mask of 8 lanes
+    let sum = vpaddq_u8(vpaddq_u8(b0, b1), vpaddq_u8(b2, b3));
+    let sum = vpaddq_u8(sum, sum);
+    vgetq_lane_u64::<0>(vreinterpretq_u64_u8(sum))
+}
+
+/// NEON version of [crate::utils::ascii::simd_lowercase] for 16 lanes.
+#[inline]
+pub fn simd_lowercase(bytes: uint8x16_t) -> uint8x16_t {
+    unsafe {
+        let upper = vandq_u8(vcgeq_u8(bytes, vdupq_n_u8(b'A')), vcleq_u8(bytes, vdupq_n_u8(b'Z')));
+        vorrq_u8(bytes, vandq_u8(upper, vdupq_n_u8(0b100000)))
+    }
+}
+
+/// NEON version of [crate::utils::simd::pad_right_zero] for 16 lanes, lanes from `to_index` are set to 0.
+#[inline]
+pub fn pad_right_zero(vector: uint8x16_t, to_index: usize) -> uint8x16_t {
+    unsafe {
+        let keep = vcltq_u8(vld1q_u8(LANE_INDEX.as_ptr()), vdupq_n_u8(to_index.min(16) as u8));
+        vandq_u8(vector, keep)
+    }
+}
+
+/// NEON version of [crate::utils::simd::pad_left_zero] for 16 lanes, lanes before `to_index` are set to 0.
+#[inline]
+pub fn pad_left_zero(vector: uint8x16_t, to_index: usize) -> uint8x16_t {
+    unsafe {
+        let keep = vcgeq_u8(vld1q_u8(LANE_INDEX.as_ptr()), vdupq_n_u8(to_index.min(16) as u8));
+        vandq_u8(vector, keep)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::simd::Simd;
+
+    use super::*;
+
+    #[test]
+    fn test_lowercase_pad() {
+        let vector = Simd::from_array(*b"HTTP/1.1\r\nHost: ");
+        let lower: Simd<u8, 16> = simd_lowercase(vector.into()).into();
+        assert_eq!(lower.as_array(), b"http/1.1\r\nhost: ");
+        let right: Simd<u8, 16> = pad_right_zero(vector.into(), 4).into();
+        assert_eq!(right.as_array(), b"HTTP\0\0\0\0\0\0\0\0\0\0\0\0");
+        let left: Simd<u8, 16> = pad_left_zero(vector.into(), 12).into();
+        assert_eq!(left.as_array(), b"\0\0\0\0\0\0\0\0\0\0\0\0st: ");
+    }
+}
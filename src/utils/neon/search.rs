@@ -0,0 +1,614 @@
+use std::arch::aarch64::{uint8x16_t, vandq_u8, vbslq_u8, vcltq_u8, vdupq_n_u8, vld1q_u8, vld1q_u8_x4, vqtbl1q_u8, vshrq_n_u8, vtstq_u8};
+use std::hint::assert_unchecked;
+use std::simd::Mask;
+
+use crate::utils::dispatch::Kernel;
+use crate::utils::ascii::simd_lowercase;
+use crate::utils::neon::{bitmask, cmpeq_mask, cmpeq_mask_ignore_case};
+use crate::utils::simd::byte_set::{ByteSet, NIBBLE_BIT};
+use crate::utils::simd::search::{equal_long, equal_long_ignore_case, Broadcast};
+use crate::utils::simd::{avx_mask_true, tail_mask};
+
+macro_rules! neon_search {
+    ($name:ident,$lanes:literal) => {
+        /// # Safety
+        /// Haystack must be 64 bytes aligned, length must be divisible by 64 and >= max(64, needle.len()),
+        /// and memory must be readable up to length + 64 + needle.len().
+        /// you can use any u8 that not included in needle as padding.
+        #[inline(never)]
+        unsafe fn $name(haystack: &[u8], needle: &[u8], broadcast: &Broadcast) -> usize {
+            let len = haystack.len();
+            let end = haystack.as_ptr().add(len - needle.len());
+            assert_unchecked(len >= 64);
+            assert_unchecked(needle.len() == $lanes);
+
+            let mut ptr = haystack.as_ptr();
+            assert_unchecked(ptr.is_aligned_to(64));
+            let needle = std::simd::Simd::<u8, $lanes>::load_select_ptr(needle.as_ptr(), Mask::splat(true), Default::default());
+            let head = vld1q_u8(broadcast.head_ptr());
+            loop {
+                let mut idx = cmpeq_mask(ptr, head);
+                while idx != 0 {
+                    let occurrence = idx.trailing_zeros() as usize;
+                    let chunk_ptr = ptr.add(occurrence);
+                    let chunk = std::simd::Simd::<u8, $lanes>::load_select_ptr(chunk_ptr, Mask::splat(true), Default::default());
+
+                    if chunk == needle {
+                        return chunk_ptr.addr() - haystack.as_ptr().addr();
+                    }
+                    idx &= idx - 1;
+                }
+
+                ptr = ptr.add(64);
+                if ptr > end {
+                    break;
+                }
+            }
+            len
+        }
+    }
+}
+
+macro_rules! neon_search_sub_1 {
+    ($name:ident,$lanes:literal) => {
+        /// # Safety
+        /// Haystack must be 64 bytes aligned, length must be divisible by 64 and >= max(64, needle.len()),
+        /// and memory must be readable up to length + 64 + needle.len().
+        /// you can use any u8 that not included in needle as padding.
+        #[inline(never)]
+        unsafe fn $name(haystack: &[u8], needle: &[u8], broadcast: &Broadcast) -> usize {
+            let len = haystack.len();
+            let end = haystack.as_ptr().add(len - needle.len());
+            assert_unchecked(len >= 64);
+            assert_unchecked(needle.len() == $lanes + 1);
+
+            let head = vld1q_u8(broadcast.head_ptr());
+            let mut ptr = haystack.as_ptr();
+            assert_unchecked(ptr.is_aligned_to(64));
+            let needle = std::simd::Simd::<u8, $lanes>::load_select_ptr(needle.as_ptr().add(1), Mask::splat(true), Default::default());
+
+            loop {
+                let mut idx = cmpeq_mask(ptr, head);
+                while idx != 0 {
+                    let occurrence = idx.trailing_zeros() as usize;
+
+                    let chunk_ptr = ptr.add(occurrence + 1);
+                    let chunk = std::simd::Simd::<u8, $lanes>::load_select_ptr(chunk_ptr, Mask::splat(true), Default::default());
+
+                    if chunk == needle {
+                        return (chunk_ptr.addr() - 1) - haystack.as_ptr().addr();
+                    }
+                    idx &= idx - 1;
+                }
+
+                ptr = ptr.add(64);
+                if ptr > end {
+                    break;
+                }
+            }
+            len
+        }
+    }
+}
+
+macro_rules! neon_search_padded {
+    ($name:ident,$lanes:literal) => {
+        /// # Safety
+        /// Haystack must be 64 bytes aligned, length must be divisible by 64 and >= max(64, needle.len()),
+        /// and memory must be readable up to length + 64 + needle.len().
+        /// you can use any u8 that not included in needle as padding.
+        #[inline(never)]
+        unsafe fn $name(haystack: &[u8], needle: &[u8], broadcast: &Broadcast) -> usize {
+            const LEN_HALF: usize = $lanes / 2;
+            let len = haystack.len();
+            let end = haystack.as_ptr().add(len - needle.len());
+            assert_unchecked(len >= 64);
+            assert_unchecked(needle.len() >= ((LEN_HALF) + 1) && needle.len() < $lanes);
+            let mut ptr = haystack.as_ptr();
+            assert_unchecked(ptr.is_aligned_to(64));
+
+            let mask = avx_mask_true!((needle.len() - LEN_HALF));
+            let mask = Mask::from_bitmask(mask);
+
+            let needle_low = std::simd::Simd::<u8, LEN_HALF>::load_select_ptr(needle.as_ptr(), Mask::splat(true), Default::default());
+            let needle_high = std::simd::Simd::<u8, LEN_HALF>::load_select_ptr(needle.as_ptr().add(LEN_HALF), mask, Default::default());
+            let head = vld1q_u8(broadcast.head_ptr());
+            loop {
+                let mut idx = cmpeq_mask(ptr, head);
+                while idx != 0 {
+                    let occurrence = idx.trailing_zeros() as usize;
+                    let chunk_ptr = ptr.add(occurrence);
+                    let chunk_low = std::simd::Simd::<u8, LEN_HALF>::load_select_ptr(chunk_ptr, Mask::splat(true), Default::default());
+                    let chunk_high = std::simd::Simd::<u8, LEN_HALF>::load_select_ptr(chunk_ptr.add(LEN_HALF), mask, Default::default());
+
+                    if chunk_low == needle_low && chunk_high == needle_high {
+                        return chunk_ptr.addr() - haystack.as_ptr().addr();
+                    }
+                    idx &= idx - 1;
+                }
+                ptr = ptr.add(64);
+                if ptr > end {
+                    break len;
+                }
+            }
+        }
+    };
+}
+
+// needle.len() == 3
+neon_search_sub_1!(index_of3, 2);
+// needle.len() == 4
+neon_search!(index_of4, 4);
+// needle.len() == 5
+neon_search_sub_1!(index_of5, 4);
+// needle.len() > 5 && needle.len() < 8
+neon_search_padded!(index_of6_lt8, 8);
+// needle.len() == 8
+neon_search!(index_of8, 8);
+// needle.len() == 9
+neon_search_sub_1!(index_of9, 8);
+// needle.len() > 9 && needle.len() < 16
+neon_search_padded!(index_of10_lt16, 16);
+neon_search!(index_of16, 16);
+// needle.len() == 17
+neon_search_sub_1!(index_of17, 16);
+// needle.len() > 17 && needle.len() < 32
+neon_search_padded!(index_of18_lt32, 32);
+neon_search!(index_of32, 32);
+// needle.len() == 33
+neon_search_sub_1!(index_of33, 32);
+// needle.len() > 33 && needle.len() < 64
+neon_search_padded!(index_of34_lt64, 64);
+neon_search!(index_of64, 64);
+neon_search_sub_1!(index_of65, 64);
+
+/// NEON version of [crate::utils::avx::search::avx_search], process 64 bytes per loop with 4 registers.
+/// # Safety
+/// Haystack must be 64 bytes aligned, length must be divisible by 64 and >= max(64, needle.len()),
+/// and memory must be readable up to length + 64 + needle.len().
+/// you can use any u8 that not included in needle as padding.
+#[inline(always)]
+pub unsafe fn neon_search<const NEEDLE_SIZE: usize>(haystack: &[u8], needle: &[u8; NEEDLE_SIZE]) -> usize {
+    let broadcast = &Broadcast::new(needle);
+    // compiler will choose best function for specific needle size
+    if NEEDLE_SIZE > 65 {
+        index_of_long(haystack, needle, broadcast)
+    } else if NEEDLE_SIZE == 65 {
+        index_of65(haystack, needle, broadcast)
+    } else if NEEDLE_SIZE == 64 {
+        index_of64(haystack, needle, broadcast)
+    } else if NEEDLE_SIZE > 33 {
+        index_of34_lt64(haystack, needle, broadcast)
+    } else if NEEDLE_SIZE == 33 {
+        index_of33(haystack, needle, broadcast)
+    } else if NEEDLE_SIZE == 32 {
+        index_of32(haystack, needle, broadcast)
+    } else if NEEDLE_SIZE > 17 {
+        index_of18_lt32(haystack, needle, broadcast)
+    } else if NEEDLE_SIZE == 17 {
+        index_of17(haystack, needle, broadcast)
+    } else if NEEDLE_SIZE == 16 {
+        index_of16(haystack, needle, broadcast)
+    } else if NEEDLE_SIZE > 9 {
+        index_of10_lt16(haystack, needle, broadcast)
+    } else if NEEDLE_SIZE == 9 {
+        index_of9(haystack, needle, broadcast)
+    } else if NEEDLE_SIZE == 8 {
+        index_of8(haystack, needle, broadcast)
+    } else if NEEDLE_SIZE > 5 {
+        index_of6_lt8(haystack, needle, broadcast)
+    } else if NEEDLE_SIZE == 5 {
+        index_of5(haystack, needle, broadcast)
+    } else if NEEDLE_SIZE == 4 {
+        index_of4(haystack, needle, broadcast)
+    } else if NEEDLE_SIZE == 3 {
+        index_of3(haystack, needle, broadcast)
+    } else if NEEDLE_SIZE == 2 {
+        index_of2(haystack, needle).unwrap_or(haystack.len())
+    } else if NEEDLE_SIZE == 1 {
+        index_of(haystack, needle[0]).unwrap_or(haystack.len())
+    } else {
+        rt_search(haystack, needle, broadcast)
+    }
+}
+
+/// Choose function from the set that [neon_search] would use for needle of `needle_len` bytes,
+/// the returned function has the same contract as [neon_search].
+pub(crate) fn kernel(needle_len: usize) -> Kernel {
+    match needle_len {
+        0 => rt_search,
+        1 => |haystack, needle, _| unsafe { index_of(haystack, needle[0]).unwrap_or(haystack.len()) },
+        2 => |haystack, needle, _| unsafe { index_of2(haystack, needle).unwrap_or(haystack.len()) },
+        3 => index_of3,
+        4 => index_of4,
+        5 => index_of5,
+        6..=7 => index_of6_lt8,
+        8 => index_of8,
+        9 => index_of9,
+        10..=15 => index_of10_lt16,
+        16 => index_of16,
+        17 => index_of17,
+        18..=31 => index_of18_lt32,
+        32 => index_of32,
+        33 => index_of33,
+        34..=63 => index_of34_lt64,
+        64 => index_of64,
+        65 => index_of65,
+        _ => index_of_long,
+    }
+}
+
+/// Filter candidate by first and last byte of needle, then compare whole needle 64 bytes at a time.
+/// # Safety
+/// Haystack must be 64 bytes aligned, length must be divisible by 64 and >= max(64, needle.len()),
+/// and memory must be readable up to length + 64 + needle.len().
+/// you can use any u8 that not included in needle as padding.
+#[inline(never)]
+unsafe fn index_of_long(haystack: &[u8], needle: &[u8], broadcast: &Broadcast) -> usize {
+    let len = haystack.len();
+    let end = haystack.as_ptr().add(len - needle.len());
+    assert_unchecked(len >= 64);
+    assert_unchecked(needle.len() > 65);
+
+    let last = needle.len() - 1;
+    let mut ptr = haystack.as_ptr();
+    assert_unchecked(ptr.is_aligned_to(64));
+    let head = vld1q_u8(broadcast.head_ptr());
+    let tail = vld1q_u8(broadcast.tail_ptr());
+    loop {
+        // last byte is loaded unaligned from the same block shifted by needle length
+        let mut idx = cmpeq_mask(ptr, head) & cmpeq_mask(ptr.add(last), tail);
+        while idx != 0 {
+            let occurrence = idx.trailing_zeros() as usize;
+            let chunk_ptr = ptr.add(occurrence);
+            if equal_long(chunk_ptr, needle) {
+                return chunk_ptr.addr() - haystack.as_ptr().addr();
+            }
+            idx &= idx - 1;
+        }
+
+        ptr = ptr.add(64);
+        if ptr > end {
+            break;
+        }
+    }
+    len
+}
+
+#[inline(never)]
+fn rt_search(haystack: &[u8], needle: &[u8], _: &Broadcast) -> usize {
+    unsafe { assert_unchecked(haystack.as_ptr().is_aligned_to(64)) };
+    memchr::memmem::find(haystack, needle).unwrap_or(haystack.len())
+}
+
+/// Find first `needle` in `haystack`, bytes after `haystack.len()` are never reported.
+/// # Safety
+/// Haystack must be 64 bytes aligned and readable up to `haystack.len()` rounded up to 64.
+#[inline(never)]
+pub unsafe fn index_of(haystack: &[u8], needle: u8) -> Option<usize> {
+    let len = haystack.len();
+    let ptr = haystack.as_ptr();
+    assert_unchecked(ptr.is_aligned_to(64));
+
+    let head = vdupq_n_u8(needle);
+    let mut offset = 0;
+    while offset < len {
+        let idx = cmpeq_mask(ptr.add(offset), head) & tail_mask(len - offset);
+        if idx != 0 {
+            return Some(offset + idx.trailing_zeros() as usize);
+        }
+        offset += 64;
+    }
+    None
+}
+
+/// Find first `needle` in `haystack`, bytes after `haystack.len()` are never reported.
+/// # Safety
+/// Haystack must be 64 bytes aligned and readable up to `haystack.len()` rounded up to 64.
+#[inline(never)]
+pub unsafe fn index_of2(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    let len = haystack.len();
+    let ptr = haystack.as_ptr();
+    assert_unchecked(ptr.is_aligned_to(64));
+    assert_unchecked(needle.len() == 2);
+    let mut carry = 0;
+
+    let head = vdupq_n_u8(needle[0]);
+    let tail = vdupq_n_u8(needle[1]);
+    let mut offset = 0;
+    while offset < len {
+        let block = ptr.add(offset);
+        let idx = cmpeq_mask(block, head);
+        // position of second byte, head at last lane of previous block is carried to first lane
+        let mask = ((idx << 1) | carry) & tail_mask(len - offset);
+        carry = idx >> 63;
+        let idx = mask & cmpeq_mask(block, tail);
+        if idx != 0 {
+            // carry only set after first block, so offset is never 0 when second byte is at first lane
+            return Some(offset + idx.trailing_zeros() as usize - 1);
+        }
+        offset += 64;
+    }
+    None
+}
+
+/// Find last `needle` in `haystack` scanning 64 bytes blocks backward, bytes after `haystack.len()` are never reported.
+/// # Safety
+/// Haystack must be 64 bytes aligned and readable up to `haystack.len()` rounded up to 64.
+#[inline(never)]
+pub unsafe fn rindex_of(haystack: &[u8], needle: u8) -> Option<usize> {
+    let len = haystack.len();
+    let ptr = haystack.as_ptr();
+    assert_unchecked(ptr.is_aligned_to(64));
+
+    let head = vdupq_n_u8(needle);
+    let mut offset = len.next_multiple_of(64);
+    while offset > 0 {
+        offset -= 64;
+        let idx = cmpeq_mask(ptr.add(offset), head) & tail_mask(len - offset);
+        if idx != 0 {
+            return Some(offset + 63 - idx.leading_zeros() as usize);
+        }
+    }
+    None
+}
+
+/// Find last `needle` in `haystack`, candidate is filtered by first byte from the last block
+/// so only bytes inside `haystack.len()` are compared.
+/// # Safety
+/// Haystack must be 64 bytes aligned and readable up to `haystack.len()` rounded up to 64.
+#[inline(never)]
+unsafe fn rindex_of_n(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    let ptr = haystack.as_ptr();
+    assert_unchecked(ptr.is_aligned_to(64));
+    assert_unchecked(needle.len() >= 2 && needle.len() <= haystack.len());
+    // last start position where whole needle is inside haystack
+    let last = haystack.len() - needle.len();
+
+    let head = vdupq_n_u8(needle[0]);
+    let mut offset = (last + 1).next_multiple_of(64);
+    while offset > 0 {
+        offset -= 64;
+        let mut idx = cmpeq_mask(ptr.add(offset), head) & tail_mask(last + 1 - offset);
+        while idx != 0 {
+            let occurrence = 63 - idx.leading_zeros() as usize;
+            if haystack.get_unchecked(offset + occurrence..offset + occurrence + needle.len()) == needle {
+                return Some(offset + occurrence);
+            }
+            idx ^= 1 << occurrence;
+        }
+    }
+    None
+}
+
+/// Reverse counterpart of [neon_search], return start of last occurrence of `needle`.
+/// # Safety
+/// Haystack must be 64 bytes aligned and readable up to `haystack.len()` rounded up to 64.
+#[inline(always)]
+pub unsafe fn neon_rfind<const NEEDLE_SIZE: usize>(haystack: &[u8], needle: &[u8; NEEDLE_SIZE]) -> Option<usize> {
+    match NEEDLE_SIZE {
+        0 => Some(haystack.len()),
+        1 => rindex_of(haystack, needle[0]),
+        _ if NEEDLE_SIZE > haystack.len() => None,
+        _ => rindex_of_n(haystack, needle),
+    }
+}
+
+/// Nibble tables of [ByteSet] loaded for `tbl` lookup.
+struct SetTables {
+    low: uint8x16_t,
+    high: uint8x16_t,
+    bit: uint8x16_t,
+}
+
+impl SetTables {
+    #[inline(always)]
+    unsafe fn new(set: &ByteSet) -> Self {
+        Self {
+            low: vld1q_u8(set.low.as_ptr()),
+            high: vld1q_u8(set.high.as_ptr()),
+            bit: vld1q_u8(NIBBLE_BIT.as_ptr()),
+        }
+    }
+
+    /// Return compare result of 16 lanes, all bits are set for byte in the set.
+    #[inline(always)]
+    unsafe fn lanes(&self, vector: uint8x16_t) -> uint8x16_t {
+        let low_nibble = vandq_u8(vector, vdupq_n_u8(0x0f));
+        let high_nibble = vshrq_n_u8::<4>(vector);
+        let row = vbslq_u8(vcltq_u8(vector, vdupq_n_u8(0x80)), vqtbl1q_u8(self.low, low_nibble), vqtbl1q_u8(self.high, low_nibble));
+        vtstq_u8(row, vqtbl1q_u8(self.bit, high_nibble))
+    }
+
+    #[inline(always)]
+    unsafe fn mask64(&self, ptr: *const u8) -> u64 {
+        let block = vld1q_u8_x4(ptr);
+        bitmask(self.lanes(block.0), self.lanes(block.1), self.lanes(block.2), self.lanes(block.3))
+    }
+}
+
+/// Return bitmask of bytes in `set` among 64 bytes start at `ptr`.
+/// # Safety
+/// `ptr` must be readable for 64 bytes.
+#[inline]
+pub unsafe fn byte_set_mask(ptr: *const u8, set: &ByteSet) -> u64 {
+    SetTables::new(set).mask64(ptr)
+}
+
+/// Find first byte of `haystack` that is in `set`, bytes after `haystack.len()` are never reported.
+/// # Safety
+/// Haystack must be 64 bytes aligned and readable up to `haystack.len()` rounded up to 64.
+#[inline(never)]
+pub unsafe fn index_of_set(haystack: &[u8], set: &ByteSet) -> Option<usize> {
+    let len = haystack.len();
+    let ptr = haystack.as_ptr();
+    assert_unchecked(ptr.is_aligned_to(64));
+
+    let tables = SetTables::new(set);
+    let mut offset = 0;
+    while offset < len {
+        let idx = tables.mask64(ptr.add(offset)) & tail_mask(len - offset);
+        if idx != 0 {
+            return Some(offset + idx.trailing_zeros() as usize);
+        }
+        offset += 64;
+    }
+    None
+}
+
+/// Return bitmask of bytes equal to `needle` among 64 bytes start at `ptr`.
+/// # Safety
+/// `ptr` must be readable for 64 bytes.
+#[inline]
+pub unsafe fn byte_mask(ptr: *const u8, needle: u8) -> u64 {
+    cmpeq_mask(ptr, vdupq_n_u8(needle))
+}
+
+/// Return bitmask of positions among 64 bytes start at `ptr` that equal first byte of needle
+/// and whose byte `last` positions later equal last byte, same filter as [index_of_long].
+/// # Safety
+/// `ptr` must be readable for `64 + last` bytes.
+#[inline]
+pub unsafe fn byte_pair_mask(ptr: *const u8, broadcast: &Broadcast, last: usize) -> u64 {
+    cmpeq_mask(ptr, vld1q_u8(broadcast.head_ptr())) & cmpeq_mask(ptr.add(last), vld1q_u8(broadcast.tail_ptr()))
+}
+
+macro_rules! neon_search_ignore_case {
+    ($name:ident,$lanes:literal) => {
+        /// Same as [neon_search] but haystack is lowercased on the fly, `needle` must be lowercase.
+        /// # Safety
+        /// Haystack must be 64 bytes aligned, length must be divisible by 64 and >= max(64, needle.len()),
+        /// and memory must be readable up to length + 64 + needle.len().
+        #[inline(never)]
+        unsafe fn $name(haystack: &[u8], needle: &[u8], broadcast: &Broadcast) -> usize {
+            let len = haystack.len();
+            let end = haystack.as_ptr().add(len - needle.len());
+            assert_unchecked(len >= 64);
+            assert_unchecked(!needle.is_empty() && needle.len() <= $lanes);
+
+            let mut ptr = haystack.as_ptr();
+            assert_unchecked(ptr.is_aligned_to(64));
+            let mask = Mask::<i8, $lanes>::from_bitmask(tail_mask(needle.len()));
+            let needle_vector = std::simd::Simd::<u8, $lanes>::load_select_ptr(needle.as_ptr(), mask, Default::default());
+            let head = vld1q_u8(broadcast.head_ptr());
+            loop {
+                let mut idx = cmpeq_mask_ignore_case(ptr, head);
+                while idx != 0 {
+                    let occurrence = idx.trailing_zeros() as usize;
+                    let chunk = std::simd::Simd::<u8, $lanes>::load_select_ptr(ptr.add(occurrence), mask, Default::default());
+                    if simd_lowercase(chunk) == needle_vector {
+                        return ptr.add(occurrence).addr() - haystack.as_ptr().addr();
+                    }
+                    idx &= idx - 1;
+                }
+
+                ptr = ptr.add(64);
+                if ptr > end {
+                    break;
+                }
+            }
+            len
+        }
+    };
+}
+
+// needle.len() <= 8
+neon_search_ignore_case!(index_of_ignore_case_le8, 8);
+// needle.len() > 8 && needle.len() <= 16
+neon_search_ignore_case!(index_of_ignore_case_le16, 16);
+// needle.len() > 16 && needle.len() <= 32
+neon_search_ignore_case!(index_of_ignore_case_le32, 32);
+// needle.len() > 32 && needle.len() <= 64
+neon_search_ignore_case!(index_of_ignore_case_le64, 64);
+
+/// Same as [index_of_long] but haystack is lowercased on the fly, `needle` must be lowercase.
+/// # Safety
+/// Haystack must be 64 bytes aligned, length must be divisible by 64 and >= max(64, needle.len()),
+/// and memory must be readable up to length + 64 + needle.len().
+#[inline(never)]
+unsafe fn index_of_long_ignore_case(haystack: &[u8], needle: &[u8], broadcast: &Broadcast) -> usize {
+    let len = haystack.len();
+    let end = haystack.as_ptr().add(len - needle.len());
+    assert_unchecked(len >= 64);
+    assert_unchecked(needle.len() > 64);
+
+    let last = needle.len() - 1;
+    let mut ptr = haystack.as_ptr();
+    assert_unchecked(ptr.is_aligned_to(64));
+    let head = vld1q_u8(broadcast.head_ptr());
+    let tail = vld1q_u8(broadcast.tail_ptr());
+    loop {
+        let mut idx = cmpeq_mask_ignore_case(ptr, head) & cmpeq_mask_ignore_case(ptr.add(last), tail);
+        while idx != 0 {
+            let occurrence = idx.trailing_zeros() as usize;
+            if equal_long_ignore_case(ptr.add(occurrence), needle) {
+                return ptr.add(occurrence).addr() - haystack.as_ptr().addr();
+            }
+            idx &= idx - 1;
+        }
+
+        ptr = ptr.add(64);
+        if ptr > end {
+            break;
+        }
+    }
+    len
+}
+
+/// Choose case-insensitive search function for needle of `needle_len` bytes,
+/// the returned function has the same contract as [neon_search] and expect lowercase needle.
+pub(crate) fn kernel_ignore_case(needle_len: usize) -> Kernel {
+    match needle_len {
+        0 => |_, _, _| 0,
+        1..=8 => index_of_ignore_case_le8,
+        9..=16 => index_of_ignore_case_le16,
+        17..=32 => index_of_ignore_case_le32,
+        33..=64 => index_of_ignore_case_le64,
+        _ => index_of_long_ignore_case,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::BufferSlice;
+
+    static DATA: &[u8] = b"GET /index.html HTTP/1.1\r\nHost: developer.mozilla.org\r\nAccept-Language: fr\r\nUser-Agent: Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/108.0.0.0 Safari/537.36\r\n\r\n";
+
+    fn check<const N: usize>(from: usize) {
+        let haystack = BufferSlice::<4096, 4096>::from_slice(DATA);
+        // search over whole block, padding is zero
+        let blocks = unsafe { std::slice::from_raw_parts(haystack.ptr(), haystack.len().next_multiple_of(64)) };
+        let needle: [u8; N] = DATA[from..from + N].try_into().unwrap();
+        let expected = memchr::memmem::find(DATA, &needle).unwrap();
+        assert_eq!(expected, unsafe { neon_search(blocks, &needle) }, "needle size {N}");
+
+        let mut missing = needle;
+        missing[N - 1] = 0x01;
+        assert_eq!(blocks.len(), unsafe { neon_search(blocks, &missing) }, "needle size {N}");
+    }
+
+    #[test]
+    fn test_neon_search() {
+        check::<1>(63);
+        check::<2>(63);
+        check::<3>(62);
+        check::<4>(58);
+        check::<5>(40);
+        check::<7>(64);
+        check::<8>(100);
+        check::<9>(101);
+        check::<12>(60);
+        check::<16>(33);
+        check::<17>(31);
+        check::<24>(90);
+        check::<32>(64);
+        check::<33>(70);
+        check::<48>(20);
+        check::<64>(55);
+        check::<65>(56);
+        check::<66>(20);
+        check::<100>(50);
+        check::<128>(60);
+    }
+}
@@ -3,7 +3,29 @@ use std::simd::{LaneCount, Mask, Simd, SupportedLaneCount};
 use std::simd::cmp::SimdPartialEq;
 
 pub mod aligned;
+pub mod byte_set;
+pub mod decode;
 pub mod iter;
+pub mod search;
+pub mod validate;
+
+macro_rules! avx_mask_true {
+    ($count:expr) => {
+        ((1 << $count) - 1)
+    }
+}
+
+pub(crate) use avx_mask_true;
+
+/// Bitmask of lanes inside 64 bytes block that still before the end, `remain` is length minus block offset.
+#[inline(always)]
+pub const fn tail_mask(remain: usize) -> u64 {
+    if remain >= 64 {
+        u64::MAX
+    } else {
+        avx_mask_true!(remain)
+    }
+}
 
 #[inline]
 fn pad_right_zero_runtime<const LANES: usize>(simd: Simd<u8, LANES>, to_index: usize) -> Simd<u8, LANES>
@@ -86,6 +108,7 @@ where
     const_eval_select((simd, to_index), pad_left_zero_comptime, pad_left_zero_runtime)
 }
 
+
 #[inline]
 pub fn move_left_zero_end<const LANES: usize>(simd: Simd<u8, LANES>, amount: usize) -> Simd<u8, LANES>
 where
@@ -127,4 +150,4 @@ where
     }
 
     LANES
-}
\ No newline at end of file
+}
@@ -1,3 +1,6 @@
+#[cfg(target_arch = "aarch64")]
+use std::arch::aarch64::{float32x4_t, float32x4x2_t, float32x4x4_t, int8x16_t, int8x16x2_t, int8x16x4_t, uint8x16_t, uint8x16x2_t, uint8x16x4_t, vld1q_u8, vld1q_u8_x2, vld1q_u8_x4, vst1q_u8_x4};
+#[cfg(target_arch = "x86_64")]
 use std::arch::x86_64::{__m128, __m128d, __m128i, __m256, __m256d, __m256i, __m512, __m512d, __m512i, _mm256_load_si256, _mm512_load_epi64, _mm512_load_si512, _mm512_store_epi64, _mm_load_si128};
 use std::cmp::Ordering;
 use std::fmt::{Binary, Formatter, UpperHex, Write};
@@ -139,10 +142,20 @@ macro_rules! aligned {
     };
 }
 
+#[cfg(target_arch = "x86_64")]
 aligned!(Aligned16, 16, __m128i, __m128, __m128d, _mm_load_si128);
+#[cfg(target_arch = "x86_64")]
 aligned!(Aligned32, 32, __m256i, __m256, __m256d, _mm256_load_si256);
+#[cfg(target_arch = "x86_64")]
 aligned!(Aligned64, 64, __m512i, __m512, __m512d, _mm512_load_si512);
 
+#[cfg(target_arch = "aarch64")]
+aligned!(Aligned16, 16, uint8x16_t, int8x16_t, float32x4_t, vld1q_u8);
+#[cfg(target_arch = "aarch64")]
+aligned!(Aligned32, 32, uint8x16x2_t, int8x16x2_t, float32x4x2_t, vld1q_u8_x2);
+#[cfg(target_arch = "aarch64")]
+aligned!(Aligned64, 64, uint8x16x4_t, int8x16x4_t, float32x4x4_t, vld1q_u8_x4);
+
 #[derive(PartialEq, Hash, Eq, Copy, Clone, Debug)]
 #[repr(align(64))]
 pub enum Aligned {
@@ -150,6 +163,7 @@ pub enum Aligned {
     Aligned32(Aligned32),
     Aligned64(Aligned64),
 }
+#[cfg(target_arch = "x86_64")]
 impl Aligned64 {
     pub unsafe fn load_vector(&self) -> __m512i {
         _mm512_load_epi64(self.0.as_ptr() as *const i64)
@@ -158,4 +172,15 @@ impl Aligned64 {
     pub unsafe fn store_vector(&mut self, vector: __m512i) {
         _mm512_store_epi64(self.0.as_mut_ptr() as *mut i64, vector);
     }
+}
+
+#[cfg(target_arch = "aarch64")]
+impl Aligned64 {
+    pub unsafe fn load_vector(&self) -> uint8x16x4_t {
+        vld1q_u8_x4(self.0.as_ptr())
+    }
+
+    pub unsafe fn store_vector(&mut self, vector: uint8x16x4_t) {
+        vst1q_u8_x4(self.0.as_mut_ptr(), vector);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,114 @@
+use std::simd::cmp::{SimdPartialEq, SimdPartialOrd};
+use std::simd::Simd;
+
+use crate::utils::simd::tail_mask;
+
+const PROCESS_SIZE: usize = 64;
+
+/// Bit of high nibble inside a row of [ByteSet], indexed by high nibble.
+pub(crate) static NIBBLE_BIT: [u8; 16] = [1, 2, 4, 8, 16, 32, 64, 128, 1, 2, 4, 8, 16, 32, 64, 128];
+
+/// Set of any bytes, stored as nibble tables so membership of a whole block is checked with two table lookups
+/// (`pshufb`/`tbl`) per vector.
+///
+/// Row of each table is indexed by low nibble, bit of the row is high nibble modulo 8,
+/// `low` hold bytes with high nibble 0..8 and `high` hold bytes with high nibble 8..16.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C, align(16))]
+pub struct ByteSet {
+    pub(crate) low: [u8; 16],
+    pub(crate) high: [u8; 16],
+}
+
+impl ByteSet {
+    pub const EMPTY: ByteSet = ByteSet { low: [0; 16], high: [0; 16] };
+
+    pub const fn new(bytes: &[u8]) -> Self {
+        let mut set = Self::EMPTY;
+        let mut i = 0;
+        while i < bytes.len() {
+            set = set.with(bytes[i]);
+            i += 1;
+        }
+        set
+    }
+
+    /// Build set of every byte matched by `predicate`.
+    pub fn from_fn(predicate: impl Fn(u8) -> bool) -> Self {
+        (0..=u8::MAX).filter(|&byte| predicate(byte)).fold(Self::EMPTY, Self::with)
+    }
+
+    #[inline]
+    pub const fn with(mut self, byte: u8) -> Self {
+        let row = (byte & 0x0f) as usize;
+        let bit = 1 << ((byte >> 4) & 7);
+        if byte < 0x80 {
+            self.low[row] |= bit;
+        } else {
+            self.high[row] |= bit;
+        }
+        self
+    }
+
+    #[inline]
+    pub const fn contains(&self, byte: u8) -> bool {
+        let row = if byte < 0x80 { self.low[(byte & 0x0f) as usize] } else { self.high[(byte & 0x0f) as usize] };
+        row & (1 << ((byte >> 4) & 7)) != 0
+    }
+}
+
+#[inline(always)]
+fn splat_table(table: &[u8; 16]) -> Simd<u8, PROCESS_SIZE> {
+    Simd::from_array(std::array::from_fn(|i| table[i % 16]))
+}
+
+/// Portable version of [crate::utils::avx::search::byte_set_mask], return bitmask of bytes in `set`.
+/// # Safety
+/// `ptr` must be readable for 64 bytes.
+#[inline(always)]
+pub unsafe fn byte_set_mask(ptr: *const u8, set: &ByteSet) -> u64 {
+    let block = ptr.cast::<Simd<u8, PROCESS_SIZE>>().read_unaligned();
+    let low_nibble = block & Simd::splat(0x0f);
+    let high_nibble = block >> Simd::splat(4);
+    let row = high_nibble
+        .simd_lt(Simd::splat(8))
+        .select(splat_table(&set.low).swizzle_dyn(low_nibble), splat_table(&set.high).swizzle_dyn(low_nibble));
+    let bit = splat_table(&NIBBLE_BIT).swizzle_dyn(high_nibble);
+    (row & bit).simd_ne(Simd::splat(0)).to_bitmask()
+}
+
+/// Portable version of [crate::utils::avx::search::index_of_set].
+/// # Safety
+/// Haystack must be 64 bytes aligned and readable up to `haystack.len()` rounded up to 64.
+#[inline(always)]
+pub unsafe fn index_of_set(haystack: &[u8], set: &ByteSet) -> Option<usize> {
+    let len = haystack.len();
+    let ptr = haystack.as_ptr();
+    let mut offset = 0;
+    while offset < len {
+        let idx = byte_set_mask(ptr.add(offset), set) & tail_mask(len - offset);
+        if idx != 0 {
+            return Some(offset + idx.trailing_zeros() as usize);
+        }
+        offset += PROCESS_SIZE;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_set() {
+        let set = ByteSet::new(b"\r\n: \0\x7f\x80\xff");
+        for byte in 0..=u8::MAX {
+            assert_eq!(b"\r\n: \0\x7f\x80\xff".contains(&byte), set.contains(byte), "{byte}");
+        }
+        assert_eq!(ByteSet::from_fn(|byte| byte.is_ascii_control()), ByteSet::new(&(0..=u8::MAX).filter(u8::is_ascii_control).collect::<Vec<_>>()));
+
+        let block: [u8; 64] = std::array::from_fn(|i| (i * 4 + 1) as u8);
+        let expected = (0..64).filter(|&i| set.contains(block[i])).fold(0u64, |mask, i| mask | 1 << i);
+        assert_eq!(expected, unsafe { byte_set_mask(block.as_ptr(), &set) });
+    }
+}
@@ -0,0 +1,233 @@
+use std::simd::cmp::{SimdPartialEq, SimdPartialOrd};
+use std::simd::{Mask, Simd};
+
+use crate::utils::simd::tail_mask;
+
+const PROCESS_SIZE: usize = 64;
+
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// `%` is not followed by 2 hex digits, value is offset of `%` in the source.
+    #[error("invalid percent escape at byte {0}")]
+    InvalidEscape(usize),
+    /// Escape decodes to ASCII control byte like `%00` or `%0A`, value is offset of `%` in the source.
+    #[error("control byte escape at byte {0}")]
+    ControlByte(usize),
+    /// Decoded bytes are not UTF-8, value is offset in decoded bytes.
+    #[error("invalid UTF-8 at byte {0}")]
+    InvalidUtf8(usize),
+}
+
+/// Load 64 bytes block start at `pos`, bytes after end of `bytes` are 0.
+#[inline(always)]
+fn load(bytes: &[u8], pos: usize) -> Simd<u8, PROCESS_SIZE> {
+    match bytes.get(pos..pos + PROCESS_SIZE) {
+        Some(block) => Simd::from_slice(block),
+        None => Simd::load_or_default(&bytes[pos..]),
+    }
+}
+
+#[inline(always)]
+const fn hex(byte: u8) -> Option<u8> {
+    match byte {
+        b'0'..=b'9' => Some(byte - b'0'),
+        b'a'..=b'f' => Some(byte - b'a' + 10),
+        b'A'..=b'F' => Some(byte - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// Decode `%XX` escapes of `bytes` in place and optionally `+` to space for query string,
+/// run without escape is moved 64 bytes at a time. Return length of decoded bytes at the start of `bytes`.
+///
+/// Escape of control byte (0x00-0x1F and 0x7F) is rejected, raw control bytes are already rejected by the parser
+/// so decoded bytes never contain them.
+pub fn percent_decode_in_place(bytes: &mut [u8], plus_as_space: bool) -> Result<usize, DecodeError> {
+    let len = bytes.len();
+    let mut read = 0;
+    let mut write = 0;
+    while read < len {
+        let block = load(bytes, read);
+        let special = block.simd_eq(Simd::splat(b'%')) | (Mask::splat(plus_as_space) & block.simd_eq(Simd::splat(b'+')));
+        let mask = special.to_bitmask() & tail_mask(len - read);
+        let run = if mask == 0 { (len - read).min(PROCESS_SIZE) } else { mask.trailing_zeros() as usize };
+        // nothing to move until first escape
+        if read != write {
+            bytes.copy_within(read..read + run, write);
+        }
+        read += run;
+        write += run;
+        if mask == 0 {
+            continue;
+        }
+        if bytes[read] == b'+' {
+            bytes[write] = b' ';
+            read += 1;
+        } else {
+            let high = bytes.get(read + 1).and_then(|&byte| hex(byte));
+            let low = bytes.get(read + 2).and_then(|&byte| hex(byte));
+            let (Some(high), Some(low)) = (high, low) else {
+                return Err(DecodeError::InvalidEscape(read));
+            };
+            let byte = high << 4 | low;
+            if byte.is_ascii_control() {
+                return Err(DecodeError::ControlByte(read));
+            }
+            bytes[write] = byte;
+            read += 3;
+        }
+        write += 1;
+    }
+    Ok(write)
+}
+
+/// Load 64 bytes block of `bytes` shifted right by `N` lanes, so lane `i` is byte at `pos + i - N`.
+/// Bytes before the start and after the end are 0.
+#[inline(always)]
+fn load_previous<const N: usize>(bytes: &[u8], pos: usize) -> Simd<u8, PROCESS_SIZE> {
+    if pos >= N {
+        load(bytes, pos - N)
+    } else {
+        // only the first block, lanes before the start are rotated in from the end and cleared
+        let block = load(bytes, 0).rotate_elements_right::<N>();
+        Mask::from_bitmask(u64::MAX << N).select(block, Simd::splat(0))
+    }
+}
+
+/// Bitmask of lanes that break UTF-8 in 64 bytes block start at `pos`, lanes are compared with 3 bytes before them
+/// so sequence across blocks is checked without carrying state.
+#[inline(always)]
+fn utf8_error_mask(bytes: &[u8], pos: usize) -> u64 {
+    let current = load(bytes, pos);
+    let prev1 = load_previous::<1>(bytes, pos);
+    let prev2 = load_previous::<2>(bytes, pos);
+    let prev3 = load_previous::<3>(bytes, pos);
+    let between = |vector: Simd<u8, PROCESS_SIZE>, low: u8, high: u8| vector.simd_ge(Simd::splat(low)) & vector.simd_le(Simd::splat(high));
+
+    // byte must be continuation exactly when one of 3 bytes before it started a longer sequence
+    let continuation = between(current, 0x80, 0xbf);
+    let expected = prev1.simd_ge(Simd::splat(0xc0)) | prev2.simd_ge(Simd::splat(0xe0)) | prev3.simd_ge(Simd::splat(0xf0));
+    // 0xc0 and 0xc1 are always overlong, from 0xf5 are above U+10FFFF
+    let invalid = between(current, 0xc0, 0xc1) | current.simd_ge(Simd::splat(0xf5));
+    // second byte range depends on lead byte: overlong 3 and 4 bytes, surrogates and above U+10FFFF
+    let second = (prev1.simd_eq(Simd::splat(0xe0)) & current.simd_lt(Simd::splat(0xa0)))
+        | (prev1.simd_eq(Simd::splat(0xed)) & current.simd_ge(Simd::splat(0xa0)))
+        | (prev1.simd_eq(Simd::splat(0xf0)) & current.simd_lt(Simd::splat(0x90)))
+        | (prev1.simd_eq(Simd::splat(0xf4)) & current.simd_ge(Simd::splat(0x90)));
+    ((continuation ^ expected) | invalid | second).to_bitmask()
+}
+
+/// Offset of the first invalid byte, every block before `pos` is valid.
+#[cold]
+fn invalid_utf8(bytes: &[u8], pos: usize) -> DecodeError {
+    // start of the character that may cross into block at `pos`, it begin at most 3 bytes before
+    let mut start = pos.saturating_sub(3);
+    while start < pos && (bytes[start] as i8) < -64 {
+        start += 1;
+    }
+    let valid_up_to = std::str::from_utf8(&bytes[start..]).err().map_or(bytes.len() - start, |error| error.valid_up_to());
+    DecodeError::InvalidUtf8(start + valid_up_to)
+}
+
+/// Validate UTF-8 of `bytes` 64 bytes at a time, every lane is checked against 3 bytes before it
+/// for continuation, overlong, surrogate and out of range sequences.
+pub fn validate_utf8(bytes: &[u8]) -> Result<&str, DecodeError> {
+    let len = bytes.len();
+    let mut pos = 0;
+    while pos < len {
+        let block = load(bytes, pos);
+        // ASCII block is valid when no sequence is started right before it
+        let ascii = block.simd_lt(Simd::splat(0x80)).all() && load_previous::<3>(bytes, pos).simd_lt(Simd::splat(0xc0)).all();
+        if !ascii && utf8_error_mask(bytes, pos) & tail_mask(len - pos) != 0 {
+            return Err(invalid_utf8(bytes, pos));
+        }
+        pos += PROCESS_SIZE;
+    }
+    // sequence is unfinished at the end
+    let unfinished = |back: usize, lead: u8| len >= back && bytes[len - back] >= lead;
+    if unfinished(1, 0xc0) || unfinished(2, 0xe0) || unfinished(3, 0xf0) {
+        return Err(invalid_utf8(bytes, len.saturating_sub(3)));
+    }
+    // # Safety
+    // all bytes are validated above
+    Ok(unsafe { std::str::from_utf8_unchecked(bytes) })
+}
+
+/// Percent-decode request path in place then validate it as UTF-8, return view of the decoded path
+/// at the start of `bytes`, bytes after it are left unspecified.
+#[inline]
+pub fn decode_path(bytes: &mut [u8], plus_as_space: bool) -> Result<&str, DecodeError> {
+    let len = percent_decode_in_place(bytes, plus_as_space)?;
+    validate_utf8(&bytes[..len])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(path: &str, plus_as_space: bool) -> Result<String, DecodeError> {
+        let mut bytes = path.as_bytes().to_vec();
+        decode_path(&mut bytes, plus_as_space).map(str::to_string)
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(decode("/index.html", false).as_deref(), Ok("/index.html"));
+        assert_eq!(decode("/caf%C3%A9/a%20b", false).as_deref(), Ok("/caf\u{e9}/a b"));
+        assert_eq!(decode("/search?q=a+b%2bc", true).as_deref(), Ok("/search?q=a b+c"));
+        assert_eq!(decode("/a+b", false).as_deref(), Ok("/a+b"));
+        assert_eq!(decode("/%zz", false), Err(DecodeError::InvalidEscape(1)));
+        assert_eq!(decode("/%4", false), Err(DecodeError::InvalidEscape(1)));
+        assert_eq!(decode("/%C3%28", false), Err(DecodeError::InvalidUtf8(1)));
+        assert_eq!(decode("/a%00", false), Err(DecodeError::ControlByte(2)));
+        assert_eq!(decode("/a%0d%0A", false), Err(DecodeError::ControlByte(2)));
+        assert_eq!(decode("/%7F", false), Err(DecodeError::ControlByte(1)));
+
+        // escapes across block boundary and long runs after them
+        let path = format!("/{}%41{}%2F{}", "a".repeat(62), "b".repeat(100), "\u{e9}".repeat(40));
+        let expected = format!("/{}A{}/{}", "a".repeat(62), "b".repeat(100), "\u{e9}".repeat(40));
+        assert_eq!(decode(&path, false), Ok(expected));
+    }
+
+    #[test]
+    fn test_validate_utf8() {
+        assert_eq!(validate_utf8(b""), Ok(""));
+        let long = format!("{}\u{1f600}{}", "a".repeat(100), "b".repeat(30));
+        assert_eq!(validate_utf8(long.as_bytes()), Ok(long.as_str()));
+        let mut invalid = long.into_bytes();
+        invalid[101] = b'x';
+        assert_eq!(validate_utf8(&invalid), Err(DecodeError::InvalidUtf8(100)));
+
+        fn expected(bytes: &[u8]) -> Result<&str, DecodeError> {
+            std::str::from_utf8(bytes).map_err(|error| DecodeError::InvalidUtf8(error.valid_up_to()))
+        }
+        // overlong, surrogate, above U+10FFFF, lone continuation and unfinished sequences
+        let cases: [&[u8]; 14] = [
+            b"\xc0\x80", b"\xc1\xbf", b"\xe0\x80\x80", b"\xe0\x9f\xbf", b"\xed\xa0\x80", b"\xf0\x8f\xbf\xbf",
+            b"\xf4\x90\x80\x80", b"\xf5\x80\x80\x80", b"\xff", b"\x80", b"\xe2\x82", b"\xf0\x9f\x98", b"\xe2\x82\xac\xac",
+            "\u{7ff}\u{800}\u{d7ff}\u{e000}\u{ffff}\u{10000}\u{10ffff}".as_bytes(),
+        ];
+        for case in cases {
+            // every position around block boundary
+            for prefix in 0..70 {
+                for suffix in [0, 1, 70] {
+                    let bytes = [b"a".repeat(prefix).as_slice(), case, &b"b".repeat(suffix)].concat();
+                    assert_eq!(validate_utf8(&bytes), expected(&bytes), "{case:x?} {prefix} {suffix}");
+                }
+            }
+        }
+
+        // mutated multi-byte text
+        let text = "a\u{e9}\u{20ac}\u{1f600}".repeat(20).into_bytes();
+        let mut state = 0x2545_f491_u32;
+        for _ in 0..2000 {
+            let mut bytes = text.clone();
+            for _ in 0..2 {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                let at = (state >> 8) as usize % bytes.len();
+                bytes[at] = (state >> 16) as u8;
+            }
+            assert_eq!(validate_utf8(&bytes), expected(&bytes), "{bytes:x?}");
+        }
+    }
+}
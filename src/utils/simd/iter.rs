@@ -1,26 +1,64 @@
 use std::hint::assert_unchecked;
 use std::simd::cmp::SimdPartialEq;
-use std::simd::{u8x8, Simd};
+use std::simd::{LaneCount, Mask, Simd, SupportedLaneCount};
 
+use crate::utils::dispatch::{self, EqMask, PairMask, SetMask};
 use crate::utils::simd;
+use crate::utils::simd::byte_set::ByteSet;
+use crate::utils::simd::search::{equal_long, Broadcast};
 
-const MAX_NEEDLE_SIZE: usize = 8;
 const PROCESS_SIZE: usize = 64;
 
+/// Compare first `needle.len()` bytes of `data` with needle, only lanes of the needle are loaded.
+#[inline(always)]
+unsafe fn equal_masked<const LANES: usize>(data: &[u8], needle: &[u8]) -> bool
+where
+    LaneCount<LANES>: SupportedLaneCount,
+{
+    let mask = Mask::from_bitmask(simd::tail_mask(needle.len()));
+    let needle = Simd::<u8, LANES>::load_select_unchecked(needle, mask, Simd::splat(0));
+    Simd::load_select_unchecked(data, mask, needle).simd_eq(needle).all()
+}
+
+/// Compare candidate with needle using the same lane classes as [crate::utils::avx::search::avx_search].
+/// # Safety
+/// `data` must be at least `needle.len()` bytes long.
+#[inline(always)]
+unsafe fn equal(data: &[u8], needle: &[u8]) -> bool {
+    match needle.len() {
+        0..=8 => equal_masked::<8>(data, needle),
+        9..=16 => equal_masked::<16>(data, needle),
+        17..=32 => equal_masked::<32>(data, needle),
+        33..=64 => equal_masked::<64>(data, needle),
+        _ => equal_long(data.as_ptr(), needle),
+    }
+}
+
+/// Iterate over start of every occurrence of needle.
+///
+/// Candidates are filtered by first and last byte of needle like the search kernels, with the filter of
+/// current instruction set, then verified by [equal]. Blocks whose last byte filter would read past
+/// the slice are filtered by first byte only.
 pub struct SimdFindIter<'a> {
     source: &'a [u8],
     needle: &'a [u8],
+    broadcast: Broadcast,
+    eq: EqMask,
+    pair: PairMask,
     match_index: u64,
     position: isize,
+    /// Smallest start of next occurrence, skip whole previous occurrence in non-overlapping mode.
+    next_start: usize,
+    overlapping: bool,
 }
 
 impl<'a> SimdFindIter<'a> {
     /// # Safety
-    /// `aligned_slice` must be aligned to 64 bytes at lease 64 bytes long, and length divisible by 64 for full aligned load.
+    /// `aligned_slice` must be aligned to 64 bytes and readable up to its length rounded up to 64,
+    /// occurrences not ending inside the length are never reported.
     pub unsafe fn new(aligned_slice: &'a [u8], needle: &'a [u8]) -> Self {
         // still check in debug mode
-        assert_unchecked(needle.len() <= MAX_NEEDLE_SIZE);
-        assert_unchecked(aligned_slice.len() >= needle.len());
+        assert_unchecked(!needle.is_empty());
         assert_unchecked(aligned_slice.as_ptr().is_aligned_to(PROCESS_SIZE));
 
         Self {
@@ -28,8 +66,20 @@ impl<'a> SimdFindIter<'a> {
             match_index: 0,
             position: -(PROCESS_SIZE as isize),
             needle,
+            broadcast: Broadcast::new(needle),
+            eq: dispatch::eq_mask(dispatch::isa()),
+            pair: dispatch::pair_mask(dispatch::isa()),
+            next_start: 0,
+            overlapping: true,
         }
     }
+
+    /// Continue search after the end of each occurrence, e.g. `aaaa` contains 2 occurrences of `aa`.
+    #[inline]
+    pub fn non_overlapping(mut self) -> Self {
+        self.overlapping = false;
+        self
+    }
 }
 
 impl<'a> Iterator for SimdFindIter<'a> {
@@ -38,27 +88,37 @@ impl<'a> Iterator for SimdFindIter<'a> {
     fn next(&mut self) -> Option<Self::Item> {
         unsafe {
             assert_unchecked(self.source.as_ptr().is_aligned_to(PROCESS_SIZE));
-            assert_unchecked(self.needle.len() <= MAX_NEEDLE_SIZE);
             assert_unchecked(!self.needle.is_empty());
         }
-        let mask = !simd::mask_false(self.needle.len());
-        let needle = unsafe { Simd::load_select_unchecked(self.needle, mask, Simd::splat(0)) };
         'start: loop {
             while self.match_index != 0 {
                 let occurrence = self.match_index.trailing_zeros() as isize;
                 self.match_index ^= 1 << occurrence;
                 let pos = ((self.position) + occurrence) as usize;
-                let data = unsafe { u8x8::load_select_unchecked(&self.source[pos..], mask, needle) };
-                if data.simd_eq(needle).all() {
+                if pos < self.next_start || pos + self.needle.len() > self.source.len() {
+                    continue;
+                }
+                if unsafe { equal(&self.source[pos..], self.needle) } {
+                    if !self.overlapping {
+                        self.next_start = pos + self.needle.len();
+                    }
                     return Some(pos);
                 }
             }
-            let prefix = Simd::splat(unsafe { self.needle.as_ptr().read() });
-            let mut pos = (self.position as usize) + PROCESS_SIZE;
+            let prefix = unsafe { self.needle.as_ptr().read() };
+            let last = self.needle.len() - 1;
+            let readable = self.source.len().next_multiple_of(PROCESS_SIZE);
+            let mut pos = (self.position + PROCESS_SIZE as isize) as usize;
             let mut match_index = 0;
             while match_index == 0 && pos < self.source.len() {
-                let head = unsafe { self.source.as_ptr().add(pos).cast::<Simd<u8, PROCESS_SIZE>>().read() };
-                match_index = head.simd_eq(prefix).to_bitmask();
+                let block = unsafe { self.source.as_ptr().add(pos) };
+                // # Safety
+                // slice is readable up to length rounded up to 64, so whole block and checked tail are readable
+                match_index = if last > 0 && pos + PROCESS_SIZE + last <= readable {
+                    unsafe { (self.pair)(block, &self.broadcast, last) }
+                } else {
+                    unsafe { (self.eq)(block, prefix) }
+                };
                 pos += PROCESS_SIZE;
                 if match_index != 0 {
                     self.position = pos as isize - PROCESS_SIZE as isize;
@@ -71,6 +131,117 @@ impl<'a> Iterator for SimdFindIter<'a> {
     }
 }
 
+/// Reverse counterpart of [SimdFindIter], yield start of every occurrence from the end,
+/// blocks are scanned backward and occurrences may overlap.
+pub struct SimdRFindIter<'a> {
+    source: &'a [u8],
+    needle: &'a [u8],
+    match_index: u64,
+    position: usize,
+}
+
+impl<'a> SimdRFindIter<'a> {
+    /// # Safety
+    /// `aligned_slice` must be aligned to 64 bytes and readable up to its length rounded up to 64,
+    /// bytes after the length are never reported.
+    pub unsafe fn new(aligned_slice: &'a [u8], needle: &'a [u8]) -> Self {
+        // still check in debug mode
+        assert_unchecked(!needle.is_empty());
+        assert_unchecked(aligned_slice.as_ptr().is_aligned_to(PROCESS_SIZE));
+
+        // block after the one contain last start position where whole needle is inside the slice
+        let position = match aligned_slice.len().checked_sub(needle.len()) {
+            Some(last) => (last + 1).next_multiple_of(PROCESS_SIZE),
+            None => 0,
+        };
+        Self {
+            source: aligned_slice,
+            match_index: 0,
+            position,
+            needle,
+        }
+    }
+}
+
+impl<'a> Iterator for SimdRFindIter<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            assert_unchecked(self.source.as_ptr().is_aligned_to(PROCESS_SIZE));
+            assert_unchecked(!self.needle.is_empty());
+        }
+        'start: loop {
+            while self.match_index != 0 {
+                let occurrence = 63 - self.match_index.leading_zeros() as usize;
+                self.match_index ^= 1 << occurrence;
+                let pos = self.position + occurrence;
+                // candidates are never after last start, so whole needle is inside the slice
+                if unsafe { equal(&self.source[pos..], self.needle) } {
+                    return Some(pos);
+                }
+            }
+            let prefix = Simd::splat(unsafe { self.needle.as_ptr().read() });
+            while self.position > 0 {
+                self.position -= PROCESS_SIZE;
+                let head = unsafe { self.source.as_ptr().add(self.position).cast::<Simd<u8, PROCESS_SIZE>>().read() };
+                // position is never above last start, so subtraction can't overflow
+                let remain = self.source.len() - self.needle.len() + 1 - self.position;
+                self.match_index = head.simd_eq(prefix).to_bitmask() & simd::tail_mask(remain);
+                if self.match_index != 0 {
+                    continue 'start;
+                }
+            }
+            break None;
+        }
+    }
+}
+
+/// Iterate over position of every byte in a [ByteSet], each 64 bytes block is classified in one pass
+/// by classifier of current instruction set.
+pub struct SimdSetIter<'a> {
+    source: &'a [u8],
+    set: ByteSet,
+    mask: SetMask,
+    match_index: u64,
+    next_block: usize,
+}
+
+impl<'a> SimdSetIter<'a> {
+    /// # Safety
+    /// `aligned_slice` must be aligned to 64 bytes and readable up to its length rounded up to 64,
+    /// bytes after the length are never reported.
+    pub unsafe fn new(aligned_slice: &'a [u8], set: &ByteSet) -> Self {
+        assert_unchecked(aligned_slice.as_ptr().is_aligned_to(PROCESS_SIZE));
+
+        Self {
+            source: aligned_slice,
+            set: *set,
+            mask: dispatch::set_mask(dispatch::isa()),
+            match_index: 0,
+            next_block: 0,
+        }
+    }
+}
+
+impl<'a> Iterator for SimdSetIter<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.match_index == 0 {
+            if self.next_block >= self.source.len() {
+                return None;
+            }
+            let block = unsafe { (self.mask)(self.source.as_ptr().add(self.next_block), &self.set) };
+            self.match_index = block & simd::tail_mask(self.source.len() - self.next_block);
+            self.next_block += PROCESS_SIZE;
+        }
+        let occurrence = self.match_index.trailing_zeros() as usize;
+        self.match_index &= self.match_index - 1;
+        Some(self.next_block - PROCESS_SIZE + occurrence)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::buffer::BufferSlice;
@@ -87,6 +258,56 @@ mod tests {
             assert_eq!(iter.next(), Some(12));
             assert_eq!(iter.next(), Some(24));
             assert_eq!(iter.next(), None);
+            // needle cut by the end is not reported
+            assert_eq!(SimdFindIter::new(&haystack[..28], needle).collect::<Vec<_>>(), [0, 12]);
+            assert_eq!(SimdFindIter::new(&haystack[..3], needle).next(), None);
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_rev() {
+        let needle = b"hello";
+        let haystack = BufferSlice::<4096, 4096>::from_slice(b"hello world hello world hello world");
+        unsafe {
+            let mut iter = SimdRFindIter::new(&haystack, needle);
+            assert_eq!(iter.next(), Some(24));
+            assert_eq!(iter.next(), Some(12));
+            assert_eq!(iter.next(), Some(0));
+            assert_eq!(iter.next(), None);
+            // needle cut by the end is not reported
+            assert_eq!(SimdRFindIter::new(&haystack[..28], needle).collect::<Vec<_>>(), [12, 0]);
+            assert_eq!(SimdRFindIter::new(&haystack[..3], needle).next(), None);
+        }
+    }
+
+    #[test]
+    fn test_set() {
+        let haystack = BufferSlice::<4096, 4096>::from_slice(b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n");
+        let set = ByteSet::new(b"\r\n: ");
+        let expected = (0..haystack.len()).filter(|&i| set.contains(haystack[i])).collect::<Vec<_>>();
+        assert_eq!(expected, unsafe { SimdSetIter::new(&haystack, &set) }.collect::<Vec<_>>());
+        // padding after the length is never reported
+        assert_eq!([3, 5], *unsafe { SimdSetIter::new(&haystack[..10], &set) }.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_long_non_overlapping() {
+        let haystack = BufferSlice::<4096, 4096>::from_slice(b"--boundary--boundary--boundary\r\n\r\naaaa");
+        unsafe {
+            assert_eq!(SimdFindIter::new(&haystack, b"--boundary").collect::<Vec<_>>(), [0, 10, 20]);
+            assert_eq!(SimdFindIter::new(&haystack, b"aa").collect::<Vec<_>>(), [34, 35, 36]);
+            assert_eq!(SimdFindIter::new(&haystack, b"aa").non_overlapping().collect::<Vec<_>>(), [34, 36]);
+            assert_eq!(SimdFindIter::new(&haystack, b"\r\n").non_overlapping().collect::<Vec<_>>(), [30, 32]);
+            assert_eq!(SimdFindIter::new(&haystack, b"-").non_overlapping().count(), 6);
+        }
+        for needle_len in 1..=100 {
+            let data = (0..300).map(|i| b"abcdefg"[i % 7]).collect::<Vec<_>>();
+            let haystack = BufferSlice::<4096, 4096>::from_slice(&data);
+            let needle = &data[3..3 + needle_len];
+            let expected = (0..=data.len() - needle_len).filter(|&pos| data[pos..].starts_with(needle)).collect::<Vec<_>>();
+            assert_eq!(expected, unsafe { SimdFindIter::new(&haystack, needle) }.collect::<Vec<_>>(), "{needle_len}");
+            let reversed = expected.into_iter().rev().collect::<Vec<_>>();
+            assert_eq!(reversed, unsafe { SimdRFindIter::new(&haystack, needle) }.collect::<Vec<_>>(), "{needle_len}");
+        }
+    }
+}
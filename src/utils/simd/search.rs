@@ -0,0 +1,272 @@
+use std::simd::cmp::SimdPartialEq;
+use std::simd::{Mask, Simd};
+
+use crate::utils::ascii::simd_lowercase;
+use crate::utils::simd::tail_mask;
+
+const PROCESS_SIZE: usize = 64;
+
+#[inline(always)]
+unsafe fn load(ptr: *const u8) -> Simd<u8, PROCESS_SIZE> {
+    ptr.cast::<Simd<u8, PROCESS_SIZE>>().read_unaligned()
+}
+
+#[inline(always)]
+unsafe fn cmpeq_mask(ptr: *const u8, needle: Simd<u8, PROCESS_SIZE>) -> u64 {
+    load(ptr).simd_eq(needle).to_bitmask()
+}
+
+/// First and last byte of needle splat to 64 lanes, built once by [crate::search::Finder]
+/// so search kernels load them instead of broadcasting on every call.
+#[derive(Clone, Copy)]
+pub struct Broadcast {
+    pub(crate) head: Simd<u8, PROCESS_SIZE>,
+    pub(crate) tail: Simd<u8, PROCESS_SIZE>,
+}
+
+impl Broadcast {
+    #[inline(always)]
+    pub fn new(needle: &[u8]) -> Self {
+        Self {
+            head: Simd::splat(needle.first().copied().unwrap_or_default()),
+            tail: Simd::splat(needle.last().copied().unwrap_or_default()),
+        }
+    }
+
+    /// Pointer to 64 bytes aligned head vector, for kernels loading it into their own register type.
+    #[inline(always)]
+    pub(crate) fn head_ptr(&self) -> *const u8 {
+        self.head.as_array().as_ptr()
+    }
+
+    /// Pointer to 64 bytes aligned tail vector.
+    #[inline(always)]
+    pub(crate) fn tail_ptr(&self) -> *const u8 {
+        self.tail.as_array().as_ptr()
+    }
+}
+
+/// Portable version of [crate::utils::avx::search::index_of], body is always inlined
+/// so caller can compile it with target feature it needs.
+/// # Safety
+/// Haystack must be 64 bytes aligned and readable up to `haystack.len()` rounded up to 64.
+#[inline(always)]
+pub unsafe fn index_of(haystack: &[u8], needle: u8) -> Option<usize> {
+    let len = haystack.len();
+    let ptr = haystack.as_ptr();
+    let head = Simd::splat(needle);
+    let mut offset = 0;
+    while offset < len {
+        let idx = cmpeq_mask(ptr.add(offset), head) & tail_mask(len - offset);
+        if idx != 0 {
+            return Some(offset + idx.trailing_zeros() as usize);
+        }
+        offset += PROCESS_SIZE;
+    }
+    None
+}
+
+/// Portable version of [crate::utils::avx::search::index_of2].
+/// # Safety
+/// Haystack must be 64 bytes aligned and readable up to `haystack.len()` rounded up to 64.
+#[inline(always)]
+pub unsafe fn index_of2(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    let len = haystack.len();
+    let ptr = haystack.as_ptr();
+    let head = Simd::splat(needle[0]);
+    let tail = Simd::splat(needle[1]);
+    let mut carry = 0;
+    let mut offset = 0;
+    while offset < len {
+        let block = ptr.add(offset);
+        let idx = cmpeq_mask(block, head);
+        // position of second byte, head at last lane of previous block is carried to first lane
+        let mask = ((idx << 1) | carry) & tail_mask(len - offset);
+        carry = idx >> 63;
+        let idx = mask & cmpeq_mask(block, tail);
+        if idx != 0 {
+            return Some(offset + idx.trailing_zeros() as usize - 1);
+        }
+        offset += PROCESS_SIZE;
+    }
+    None
+}
+
+/// Compare `needle` with bytes start at `ptr` 64 bytes at a time, last chunk overlap with previous one.
+/// # Safety
+/// `ptr` must be readable for `needle.len()` bytes and needle.len() must be >= 64.
+#[inline(always)]
+pub unsafe fn equal_long(ptr: *const u8, needle: &[u8]) -> bool {
+    let len = needle.len();
+    let mut offset = 0;
+    while offset + PROCESS_SIZE < len {
+        if load(ptr.add(offset)) != load(needle.as_ptr().add(offset)) {
+            return false;
+        }
+        offset += PROCESS_SIZE;
+    }
+    load(ptr.add(len - PROCESS_SIZE)) == load(needle.as_ptr().add(len - PROCESS_SIZE))
+}
+
+/// Filter candidate by first and last byte of needle then compare whole needle.
+/// # Safety
+/// Haystack must be 64 bytes aligned, length must be divisible by 64 and >= max(64, needle.len()),
+/// and memory must be readable up to length + 64 + needle.len().
+/// needle.len() must be at least 2 and `broadcast` built from it.
+#[inline(always)]
+pub unsafe fn index_of_n(haystack: &[u8], needle: &[u8], broadcast: &Broadcast) -> usize {
+    let len = haystack.len();
+    let ptr = haystack.as_ptr();
+    let end = len - needle.len();
+    let last = needle.len() - 1;
+    let head = broadcast.head;
+    let tail = broadcast.tail;
+    let mut offset = 0;
+    while offset <= end {
+        let block = ptr.add(offset);
+        let mut idx = load(block).simd_eq(head).to_bitmask() & load(block.add(last)).simd_eq(tail).to_bitmask();
+        while idx != 0 {
+            let occurrence = idx.trailing_zeros() as usize;
+            if std::slice::from_raw_parts(block.add(occurrence), needle.len()) == needle {
+                return offset + occurrence;
+            }
+            idx &= idx - 1;
+        }
+        offset += PROCESS_SIZE;
+    }
+    len
+}
+
+/// Portable version of [crate::utils::avx::search::avx_search], `broadcast` must be built from `needle`.
+/// # Safety
+/// Haystack must be 64 bytes aligned, length must be divisible by 64 and >= max(64, needle.len()),
+/// and memory must be readable up to length + 64 + needle.len().
+#[inline(always)]
+pub unsafe fn search(haystack: &[u8], needle: &[u8], broadcast: &Broadcast) -> usize {
+    match needle.len() {
+        0 => 0,
+        1 => index_of(haystack, needle[0]).unwrap_or(haystack.len()),
+        2 => index_of2(haystack, needle).unwrap_or(haystack.len()),
+        _ => index_of_n(haystack, needle, broadcast),
+    }
+}
+
+/// Portable version of [crate::utils::avx::search::rindex_of].
+/// # Safety
+/// Haystack must be 64 bytes aligned and readable up to `haystack.len()` rounded up to 64.
+#[inline(always)]
+pub unsafe fn rindex_of(haystack: &[u8], needle: u8) -> Option<usize> {
+    let len = haystack.len();
+    let ptr = haystack.as_ptr();
+    let head = Simd::splat(needle);
+    let mut offset = len.next_multiple_of(PROCESS_SIZE);
+    while offset > 0 {
+        offset -= PROCESS_SIZE;
+        let idx = cmpeq_mask(ptr.add(offset), head) & tail_mask(len - offset);
+        if idx != 0 {
+            return Some(offset + 63 - idx.leading_zeros() as usize);
+        }
+    }
+    None
+}
+
+/// Portable version of [crate::utils::avx::search::avx_rfind], filter candidate by first byte of needle
+/// from the last block then compare whole needle.
+/// # Safety
+/// Haystack must be 64 bytes aligned and readable up to `haystack.len()` rounded up to 64.
+#[inline(always)]
+pub unsafe fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    let len = haystack.len();
+    match needle.len() {
+        0 => return Some(len),
+        1 => return rindex_of(haystack, needle[0]),
+        n if n > len => return None,
+        _ => {}
+    }
+    let ptr = haystack.as_ptr();
+    // last start position where whole needle is inside haystack
+    let last = len - needle.len();
+    let head = Simd::splat(needle[0]);
+    let mut offset = (last + 1).next_multiple_of(PROCESS_SIZE);
+    while offset > 0 {
+        offset -= PROCESS_SIZE;
+        let mut idx = cmpeq_mask(ptr.add(offset), head) & tail_mask(last + 1 - offset);
+        while idx != 0 {
+            let occurrence = 63 - idx.leading_zeros() as usize;
+            if haystack.get_unchecked(offset + occurrence..offset + occurrence + needle.len()) == needle {
+                return Some(offset + occurrence);
+            }
+            idx ^= 1 << occurrence;
+        }
+    }
+    None
+}
+
+/// Portable version of [crate::utils::avx::search::byte_mask].
+/// # Safety
+/// `ptr` must be readable for 64 bytes.
+#[inline(always)]
+pub unsafe fn byte_mask(ptr: *const u8, needle: u8) -> u64 {
+    cmpeq_mask(ptr, Simd::splat(needle))
+}
+
+/// Portable version of [crate::utils::avx::search::byte_pair_mask].
+/// # Safety
+/// `ptr` must be readable for `64 + last` bytes.
+#[inline(always)]
+pub unsafe fn byte_pair_mask(ptr: *const u8, broadcast: &Broadcast, last: usize) -> u64 {
+    cmpeq_mask(ptr, broadcast.head) & cmpeq_mask(ptr.add(last), broadcast.tail)
+}
+
+/// Same as [equal_long] but bytes of `ptr` are lowercased before compare, `needle` must be lowercase.
+/// # Safety
+/// `ptr` must be readable for `needle.len()` bytes and needle.len() must be >= 64.
+#[inline(always)]
+pub unsafe fn equal_long_ignore_case(ptr: *const u8, needle: &[u8]) -> bool {
+    let len = needle.len();
+    let mut offset = 0;
+    while offset + PROCESS_SIZE < len {
+        if simd_lowercase(load(ptr.add(offset))) != load(needle.as_ptr().add(offset)) {
+            return false;
+        }
+        offset += PROCESS_SIZE;
+    }
+    simd_lowercase(load(ptr.add(len - PROCESS_SIZE))) == load(needle.as_ptr().add(len - PROCESS_SIZE))
+}
+
+/// Portable version of [crate::utils::avx::search::avx_search_ignore_ascii_case], `needle` must be lowercase
+/// and `broadcast` built from it.
+/// # Safety
+/// Haystack must be 64 bytes aligned, length must be divisible by 64 and >= max(64, needle.len()),
+/// and memory must be readable up to length + 64 + needle.len().
+#[inline(always)]
+pub unsafe fn search_ignore_case(haystack: &[u8], needle: &[u8], broadcast: &Broadcast) -> usize {
+    let len = haystack.len();
+    if needle.is_empty() {
+        return 0;
+    }
+    let ptr = haystack.as_ptr();
+    let end = len - needle.len();
+    let mask = Mask::<i8, PROCESS_SIZE>::from_bitmask(tail_mask(needle.len()));
+    let needle_vector = Simd::load_select_unchecked(needle, mask, Simd::splat(0));
+    let head = broadcast.head;
+    let mut offset = 0;
+    while offset <= end {
+        let block = ptr.add(offset);
+        let mut idx = simd_lowercase(load(block)).simd_eq(head).to_bitmask();
+        while idx != 0 {
+            let occurrence = idx.trailing_zeros() as usize;
+            let found = if needle.len() <= PROCESS_SIZE {
+                simd_lowercase(Simd::load_select_ptr(block.add(occurrence), mask, Simd::splat(0))) == needle_vector
+            } else {
+                equal_long_ignore_case(block.add(occurrence), needle)
+            };
+            if found {
+                return offset + occurrence;
+            }
+            idx &= idx - 1;
+        }
+        offset += PROCESS_SIZE;
+    }
+    len
+}
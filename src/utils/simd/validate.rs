@@ -0,0 +1,99 @@
+use std::simd::cmp::{SimdPartialEq, SimdPartialOrd};
+use std::simd::{Mask, Simd};
+
+use crate::utils::simd::tail_mask;
+
+const PROCESS_SIZE: usize = 64;
+
+/// How strict header values and request target are validated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Strictness {
+    /// Only reject bytes that break framing or C strings: NUL, CR and LF.
+    Lenient,
+    /// Follow RFC 9110/9112, reject CTL and DEL, `obs-text` (0x80-0xFF) is allowed.
+    #[default]
+    Standard,
+    /// Same as [Strictness::Standard] but `obs-text` is rejected too, only printable ASCII is allowed.
+    Strict,
+}
+
+/// Return index of first byte that `invalid` mark, validate 64 bytes block at a time.
+#[inline(always)]
+fn first_invalid(bytes: &[u8], invalid: impl Fn(Simd<u8, PROCESS_SIZE>) -> Mask<i8, PROCESS_SIZE>) -> Option<usize> {
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let block = match bytes.get(pos..pos + PROCESS_SIZE) {
+            Some(block) => Simd::from_slice(block),
+            None => Simd::load_or_default(&bytes[pos..]),
+        };
+        let mask = invalid(block).to_bitmask() & tail_mask(bytes.len() - pos);
+        if mask != 0 {
+            return Some(pos + mask.trailing_zeros() as usize);
+        }
+        pos += PROCESS_SIZE;
+    }
+    None
+}
+
+/// Mark NUL, CR and LF.
+#[inline(always)]
+fn framing(block: Simd<u8, PROCESS_SIZE>) -> Mask<i8, PROCESS_SIZE> {
+    block.simd_eq(Simd::splat(0)) | block.simd_eq(Simd::splat(b'\r')) | block.simd_eq(Simd::splat(b'\n'))
+}
+
+/// Return index of first byte that is not allowed in `field-value`, which is VCHAR, SP, HTAB and `obs-text`
+/// depending on `strictness`.
+#[inline]
+pub fn first_invalid_field_value(bytes: &[u8], strictness: Strictness) -> Option<usize> {
+    match strictness {
+        Strictness::Lenient => first_invalid(bytes, framing),
+        Strictness::Standard => first_invalid(bytes, |block| {
+            (block.simd_lt(Simd::splat(b' ')) & block.simd_ne(Simd::splat(b'\t'))) | block.simd_eq(Simd::splat(0x7f))
+        }),
+        Strictness::Strict => first_invalid(bytes, |block| {
+            (block.simd_lt(Simd::splat(b' ')) & block.simd_ne(Simd::splat(b'\t'))) | block.simd_ge(Simd::splat(0x7f))
+        }),
+    }
+}
+
+/// Return index of first byte that is not allowed in `request-target`, which reject CTL, SP and DEL
+/// depending on `strictness`.
+#[inline]
+pub fn first_invalid_request_target(bytes: &[u8], strictness: Strictness) -> Option<usize> {
+    match strictness {
+        Strictness::Lenient => first_invalid(bytes, framing),
+        Strictness::Standard => first_invalid(bytes, |block| block.simd_le(Simd::splat(b' ')) | block.simd_eq(Simd::splat(0x7f))),
+        Strictness::Strict => first_invalid(bytes, |block| block.simd_le(Simd::splat(b' ')) | block.simd_ge(Simd::splat(0x7f))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let all = (0..=u8::MAX).collect::<Vec<_>>();
+        let check = |validate: fn(&[u8], Strictness) -> Option<usize>, strictness, valid: fn(u8) -> bool| {
+            for &byte in &all {
+                // offending byte at every position of 2 blocks
+                for pos in [0, 1, 63, 64, 100] {
+                    let mut bytes = vec![b'a'; 101];
+                    bytes[pos] = byte;
+                    let expected = (!valid(byte)).then_some(pos);
+                    assert_eq!(expected, validate(&bytes, strictness), "{strictness:?} {byte:#x} {pos}");
+                }
+            }
+        };
+        check(first_invalid_field_value, Strictness::Lenient, |byte| !b"\0\r\n".contains(&byte));
+        check(first_invalid_field_value, Strictness::Standard, |byte| byte == b'\t' || (byte >= b' ' && byte != 0x7f));
+        check(first_invalid_field_value, Strictness::Strict, |byte| byte == b'\t' || (b' '..0x7f).contains(&byte));
+        check(first_invalid_request_target, Strictness::Lenient, |byte| !b"\0\r\n".contains(&byte));
+        check(first_invalid_request_target, Strictness::Standard, |byte| byte > b' ' && byte != 0x7f);
+        check(first_invalid_request_target, Strictness::Strict, |byte| (b'!'..0x7f).contains(&byte));
+
+        assert_eq!(first_invalid_field_value(b"", Strictness::Strict), None);
+        assert_eq!(first_invalid_field_value("caf\u{e9}".as_bytes(), Strictness::Standard), None);
+        assert_eq!(first_invalid_field_value("caf\u{e9}".as_bytes(), Strictness::Strict), Some(3));
+    }
+}